@@ -5,6 +5,8 @@ use std::path::PathBuf;
 use tokio::fs;
 use tokio::time::{sleep, Duration};
 use real_trans::tests::audio_simulation::{AudioSimulationConfig, AudioSimulationTester};
+use real_trans::io::wav;
+use real_trans::{AudioSample, SAMPLE_RATE};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -35,13 +37,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 创建源输入音频文件（用户说中文）
     fs::create_dir_all(&tester.config().source_input_dir).await?;
     let source_audio_file = tester.config().source_input_dir.join("user_speaking_zh.wav");
-    fs::write(&source_audio_file, b"simulated chinese audio data").await?;
+    fs::write(&source_audio_file, wav::encode_wav(&synthesize_tone(220.0, 1.0), SAMPLE_RATE)).await?;
     println!("   创建源输入音频文件: {:?}", source_audio_file);
 
     // 创建目标输入音频文件（对方说英文）
     fs::create_dir_all(&tester.config().target_input_dir).await?;
     let target_audio_file = tester.config().target_input_dir.join("other_speaking_en.wav");
-    fs::write(&target_audio_file, b"simulated english audio data").await?;
+    fs::write(&target_audio_file, wav::encode_wav(&synthesize_tone(330.0, 1.0), SAMPLE_RATE)).await?;
     println!("   创建目标输入音频文件: {:?}", target_audio_file);
 
     // 5. 简单处理音频文件
@@ -51,7 +53,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut source_entries = fs::read_dir(&tester.config().source_input_dir).await?;
     while let Ok(Some(entry)) = source_entries.next_entry().await {
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm" || ext == "mp3") {
+        if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm") {
             println!("发现源输入音频文件: {:?}", path);
             
             // 读取音频文件内容（模拟真实的音频数据）
@@ -78,7 +80,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut target_entries = fs::read_dir(&tester.config().target_input_dir).await?;
     while let Ok(Some(entry)) = target_entries.next_entry().await {
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm" || ext == "mp3") {
+        if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm") {
             println!("发现目标输入音频文件: {:?}", path);
             
             // 读取音频文件内容（模拟真实的音频数据）
@@ -162,3 +164,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 生成指定频率和时长的正弦波，作为模拟语音输入
+fn synthesize_tone(frequency: f32, seconds: f32) -> Vec<AudioSample> {
+    let count = (SAMPLE_RATE as f32 * seconds) as usize;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            ((2.0 * std::f32::consts::PI * frequency * t).sin() * 8000.0) as AudioSample
+        })
+        .collect()
+}
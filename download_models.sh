@@ -24,7 +24,7 @@ echo "   - 放置到 models/ 目录下"
 echo ""
 echo "3. Silero VAD模型："
 echo "   - 访问 https://github.com/snakers4/silero-vad"
-echo "   - 下载所需的模型文件"
+echo "   - 下载 v5 的 ONNX 模型 silero_vad.onnx"
 echo "   - 放置到 models/ 目录下"
 
 echo ""
@@ -35,7 +35,7 @@ cat > models/config.json << EOF
 {
   "asr_model_path": "./models/whisper-tiny.bin",
   "mt_model_path": "./models/qwen2.5-0.5b.bin",
-  "vad_model_path": "./models/silero_vad.jit"
+  "vad_model_path": "./models/silero_vad.onnx"
 }
 EOF
 
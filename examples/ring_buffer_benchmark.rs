@@ -0,0 +1,103 @@
+//! 环形缓冲区性能对比
+//! 模拟设备回调以固定块大小读写，比较加锁的 RingBuffer 与无锁 SpscRingBuffer 的吞吐量和最坏单次耗时
+//!
+//! 运行：cargo run --release --example ring_buffer_benchmark
+
+use real_trans::{AudioSample, RingBuffer, SpscRingBuffer};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CAPACITY: usize = 8192;
+const BLOCK: usize = 480;              // 10ms @ 48kHz
+const TOTAL_SAMPLES: usize = 48_000_000; // 约1000秒音频
+
+/// 单次测试结果
+struct BenchResult {
+    elapsed: Duration,
+    worst_read: Duration,  // 消费端（模拟设备回调）单次读取的最坏耗时
+}
+
+fn bench_mutex() -> BenchResult {
+    let buffer = Arc::new(RingBuffer::<AudioSample>::new(CAPACITY));
+    let block: Vec<AudioSample> = (0..BLOCK).map(|i| i as AudioSample).collect();
+    let start = Instant::now();
+
+    let writer = {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || {
+            let mut written = 0;
+            while written < TOTAL_SAMPLES {
+                let count = buffer.write(&block[..BLOCK.min(TOTAL_SAMPLES - written)]);
+                if count == 0 {
+                    thread::yield_now();
+                }
+                written += count;
+            }
+        })
+    };
+
+    let mut read = 0;
+    let mut worst_read = Duration::ZERO;
+    while read < TOTAL_SAMPLES {
+        let call = Instant::now();
+        let data = buffer.read(BLOCK);
+        worst_read = worst_read.max(call.elapsed());
+        if data.is_empty() {
+            thread::yield_now();
+        }
+        read += data.len();
+    }
+    writer.join().unwrap();
+
+    BenchResult { elapsed: start.elapsed(), worst_read }
+}
+
+fn bench_spsc() -> BenchResult {
+    let (mut producer, mut consumer) = SpscRingBuffer::with_capacity::<AudioSample>(CAPACITY);
+    let block: Vec<AudioSample> = (0..BLOCK).map(|i| i as AudioSample).collect();
+    let start = Instant::now();
+
+    let writer = thread::spawn(move || {
+        let mut written = 0;
+        while written < TOTAL_SAMPLES {
+            let count = producer.write(&block[..BLOCK.min(TOTAL_SAMPLES - written)]);
+            if count == 0 {
+                thread::yield_now();
+            }
+            written += count;
+        }
+    });
+
+    let mut read = 0;
+    let mut worst_read = Duration::ZERO;
+    let mut output = vec![0 as AudioSample; BLOCK];
+    while read < TOTAL_SAMPLES {
+        let call = Instant::now();
+        let count = consumer.read(&mut output);
+        worst_read = worst_read.max(call.elapsed());
+        if count == 0 {
+            thread::yield_now();
+        }
+        read += count;
+    }
+    writer.join().unwrap();
+
+    BenchResult { elapsed: start.elapsed(), worst_read }
+}
+
+fn report(name: &str, result: &BenchResult) {
+    let throughput = TOTAL_SAMPLES as f64 / result.elapsed.as_secs_f64() / 1e6;
+    println!(
+        "{:<16} 总耗时 {:>8.1?}  吞吐量 {:>8.1} M采样/秒  最坏单次读取 {:>8.1?}",
+        name, result.elapsed, throughput, result.worst_read
+    );
+}
+
+fn main() {
+    println!("=== 环形缓冲区性能对比 ===");
+    println!("容量 {} 采样，块大小 {} 采样，共 {} 采样\n", CAPACITY, BLOCK, TOTAL_SAMPLES);
+
+    report("Mutex RingBuffer", &bench_mutex());
+    report("SPSC RingBuffer", &bench_spsc());
+}
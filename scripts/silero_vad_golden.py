@@ -0,0 +1,61 @@
+#!/usr/bin/env python3
+"""生成 Silero VAD 比对用的参考概率
+
+用 onnxruntime 运行官方 silero_vad.onnx（v5），按与 src/engine/silero_vad.rs 相同的方式
+逐512采样窗口推理（拼接上一窗口末尾64个采样，保持LSTM状态），把音频采样和每个窗口的
+语音概率写入JSON。然后运行被忽略的比对测试：
+
+    python3 scripts/silero_vad_golden.py models/silero_vad.onnx clip.wav golden.json
+    SILERO_VAD_MODEL=models/silero_vad.onnx SILERO_VAD_GOLDEN=golden.json \\
+        cargo test silero_vad::tests::test_matches_reference_model -- --ignored
+
+依赖：numpy、onnxruntime。音频须为16kHz单声道16位PCM WAV。
+"""
+
+import json
+import sys
+import wave
+
+import numpy as np
+import onnxruntime
+
+WINDOW_SAMPLES = 512
+CONTEXT_SAMPLES = 64
+SAMPLE_RATE = 16000
+
+
+def read_wav(path):
+    with wave.open(path, "rb") as wav:
+        if wav.getframerate() != SAMPLE_RATE or wav.getnchannels() != 1 or wav.getsampwidth() != 2:
+            sys.exit(f"{path}: expected 16 kHz mono 16-bit PCM")
+        return np.frombuffer(wav.readframes(wav.getnframes()), dtype="<i2")
+
+
+def main():
+    if len(sys.argv) != 4:
+        sys.exit(f"usage: {sys.argv[0]} silero_vad.onnx clip.wav golden.json")
+    model_path, wav_path, out_path = sys.argv[1:]
+
+    samples = read_wav(wav_path)
+    samples = samples[: len(samples) // WINDOW_SAMPLES * WINDOW_SAMPLES]
+    audio = samples.astype(np.float32) / 32768.0
+
+    session = onnxruntime.InferenceSession(model_path, providers=["CPUExecutionProvider"])
+    state = np.zeros((2, 1, 128), dtype=np.float32)
+    context = np.zeros(CONTEXT_SAMPLES, dtype=np.float32)
+    sr = np.array(SAMPLE_RATE, dtype=np.int64)
+    probabilities = []
+    for start in range(0, len(audio), WINDOW_SAMPLES):
+        window = audio[start : start + WINDOW_SAMPLES]
+        x = np.concatenate([context, window])[np.newaxis, :]
+        output, state = session.run(None, {"input": x, "state": state, "sr": sr})
+        probabilities.append(float(output[0][0]))
+        context = window[-CONTEXT_SAMPLES:]
+
+    with open(out_path, "w") as out:
+        json.dump({"samples": samples.tolist(), "probabilities": probabilities}, out)
+    print(f"{len(probabilities)} windows written to {out_path}")
+
+
+if __name__ == "__main__":
+    main()
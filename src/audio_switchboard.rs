@@ -2,12 +2,29 @@
 //! 实现全双工实时双向语音同传的音频路由
 
 use std::sync::{Arc};
-use std::collections::VecDeque;
 use tokio::sync::{mpsc, Mutex as AsyncMutex};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
 use tokio::time::{sleep, Duration};
 use crate::io::audio_device::{AudioDevice, AudioSample};
 use crate::engine::translation_pipeline::TranslationPipeline;
-use crate::bidirectional_translator::{BidirectionalTranslator};
+use crate::bidirectional_translator::{BidirectionalTranslator, TranslationDirection};
+use crate::core::ring_buffer::{OverflowPolicy, RingBuffer};
+use crate::core::tap_buffer::{LagPolicy, TapBuffer, TapError, TapReader};
+use crate::core::sample::{Ditherer, Sample};
+use crate::dsp::comfort_noise::{ComfortNoise, ComfortNoiseConfig};
+use crate::dsp::ducking_mixer::{DuckingConfig, DuckingMixer};
+use crate::dsp::echo_canceller::EchoCanceller;
+use crate::dsp::level_meter::{LevelMeter, LevelMeterConfig, LevelReading};
+use crate::dsp::noise_suppressor::NoiseSuppressor;
+use crate::dsp::processor::DspChain;
+use crate::dsp::time_stretch::CatchUpPolicy;
+use crate::engine::tts::TtsResult;
+use crate::engine::vad::VadConfig;
+use crate::AudioFormat;
+
+/// 设备回调与翻译任务之间的缓冲容量（采样数）
+const SWITCHBOARD_BUFFER_SIZE: usize = crate::DEFAULT_RING_BUFFER_SIZE * 4;
 
 /// 音频交换机状态
 #[derive(Debug, Clone, PartialEq)]
@@ -18,6 +35,15 @@ pub enum AudioSwitchboardStatus {
     Correcting,          // 修正翻译结果
 }
 
+/// 交换机中可以接入电平表的音频流
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterPoint {
+    PhysicalMic,     // 物理麦克风原始采集
+    SystemLoopback,  // 系统环回原始采集（对方的声音）
+    VirtualMic,      // 写入虚拟麦克风的混音输出
+    Headphones,      // 写入物理耳机的混音输出
+}
+
 /// 音频交换机 - 管理四个音频流的路由
 pub struct AudioSwitchboard {
     /// 发送端：物理麦克风 -> 虚拟麦克风
@@ -26,10 +52,10 @@ pub struct AudioSwitchboard {
     inbound_pipeline: Option<Arc<AsyncMutex<TranslationPipeline>>>,
     /// 物理麦克风设备
     physical_mic: Option<Box<dyn AudioDevice>>,
-    /// 物理耳机设备
-    physical_headphones: Option<Box<dyn AudioDevice>>,
+    /// 物理耳机输出路径（系统环回原声 + 译文语音）
+    physical_headphones: Arc<std::sync::Mutex<OutputPath>>,
     /// 虚拟音频设备
-    virtual_cable_input: Option<Box<dyn AudioDevice>>,  // 虚拟线缆输入端（会议软件的输入源）
+    virtual_cable_input: Arc<std::sync::Mutex<OutputPath>>,  // 虚拟线缆输入端（会议软件的输入源）：麦克风原声 + 译文语音
     virtual_cable_output: Option<Box<dyn AudioDevice>>, // 虚拟线缆输出端（接收会议声音）
     /// 状态管理
     status: AudioSwitchboardStatus,
@@ -37,13 +63,107 @@ pub struct AudioSwitchboard {
     control_tx: Option<mpsc::UnboundedSender<AudioControl>>,
     /// 用于克隆接收器的Arc包装
     control_rx_clone: Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<AudioControl>>>>,
-    /// 音频缓冲区
-    outbound_buffer: Arc<AsyncMutex<VecDeque<AudioSample>>>,
-    inbound_buffer: Arc<AsyncMutex<VecDeque<AudioSample>>>,
+    /// 音频缓冲区：设备回调写入，翻译任务异步按帧读取
+    outbound_buffer: Arc<RingBuffer<AudioSample>>,
+    inbound_buffer: Arc<RingBuffer<AudioSample>>,
+    /// 物理麦克风分接：供电平表、录音等附加消费者独立读取
+    mic_tap: Arc<TapBuffer<AudioSample>>,
+    /// 系统环回分接
+    loopback_tap: Arc<TapBuffer<AudioSample>>,
+    /// 虚拟麦克风输出分接
+    virtual_mic_tap: Arc<TapBuffer<AudioSample>>,
+    /// 物理耳机输出分接：回声消除的参考信号
+    headphone_tap: Arc<TapBuffer<AudioSample>>,
+    /// 译文语音的追赶策略
+    catch_up: CatchUpPolicy,
+    /// 每条输出路径上串行化 `play_translation`：读取积压到译文入队之间不能插入另一段译文
+    virtual_mic_translation: AsyncMutex<()>,
+    headphone_translation: AsyncMutex<()>,
+    /// 每个方向的前端处理链（设备采集之后、送入VAD/ASR之前）
+    outbound_dsp: Arc<std::sync::Mutex<DspChain>>,
+    inbound_dsp: Arc<std::sync::Mutex<DspChain>>,
+    /// 消费音频缓冲区的翻译任务
+    consumer_tasks: Vec<JoinHandle<()>>,
     /// 翻译器实例
     translator: Arc<AsyncMutex<BidirectionalTranslator>>,
 }
 
+/// 输出路径：输出设备及其前面的闪避混音器
+///
+/// 原声按采集节奏逐帧混入排队的译文语音后写入设备；设备按实时速度消费，
+/// 由此估计播放队列的积压。没有译文且混音结果静音时可填充舒适噪声。
+struct OutputPath {
+    device: Option<Box<dyn AudioDevice>>,
+    mixer: DuckingMixer,
+    comfort_noise: Option<ComfortNoise>,
+    tap: Option<Arc<TapBuffer<AudioSample>>>,  // 写入设备的音频同时写入分接（回声消除参考）
+    queued_until: Option<std::time::Instant>,  // 已写入设备的音频预计播完的时刻
+    ditherer: Ditherer,
+}
+
+impl OutputPath {
+    fn new(tap: Option<Arc<TapBuffer<AudioSample>>>, comfort_noise: Option<ComfortNoise>) -> Self {
+        OutputPath {
+            device: None,
+            mixer: DuckingMixer::new(crate::SAMPLE_RATE),
+            comfort_noise,
+            tap,
+            queued_until: None,
+            ditherer: Ditherer::new(),
+        }
+    }
+
+    /// 测量舒适噪声的背景谱（送入用户麦克风的原始采集音频）
+    fn measure_background(&mut self, audio_data: &[AudioSample]) {
+        if let Some(ref mut comfort) = self.comfort_noise {
+            comfort.measure_audio(audio_data);
+        }
+    }
+
+    /// 直接写入设备（不经过混音）
+    fn play(&mut self, audio_data: &[AudioSample]) -> Result<usize, Box<dyn std::error::Error>> {
+        if let Some(ref tap) = self.tap {
+            tap.write(audio_data);
+        }
+
+        // 新音频排在已排队音频之后
+        let now = std::time::Instant::now();
+        let start = self.queued_until.map_or(now, |until| until.max(now));
+        self.queued_until = Some(start + AudioFormat::PIPELINE.duration_of(audio_data.len()));
+
+        match self.device {
+            Some(ref mut device) => device.play_audio(audio_data),
+            None => Ok(0),
+        }
+    }
+
+    /// 把一帧原声与排队的译文混音后写入设备
+    fn mix_original(&mut self, original: &[AudioSample]) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut mixed: Vec<f32> = original.iter().map(|s| s.to_f32()).collect();
+        let translating = self.mixer.is_translating();
+        self.mixer.mix(&mut mixed);
+
+        // 译文一到立即停止舒适噪声
+        if let Some(ref mut comfort) = self.comfort_noise {
+            if translating {
+                comfort.stop();
+            } else {
+                comfort.fill(&mut mixed);
+            }
+        }
+
+        let mixed: Vec<AudioSample> = mixed.iter().map(|&value| self.ditherer.quantize(value)).collect();
+        self.play(&mixed)
+    }
+
+    /// 尚未播放的音频时长：设备队列 + 混音器中排队的译文
+    fn backlog(&self) -> Duration {
+        let queued = self.queued_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(std::time::Instant::now()));
+        queued + AudioFormat::PIPELINE.duration_of(self.mixer.pending_translation())
+    }
+}
+
 /// 音频控制消息
 #[derive(Debug)]
 pub enum AudioControl {
@@ -64,19 +184,34 @@ impl AudioSwitchboard {
         
         // 创建双向翻译器
         let translator = Arc::new(AsyncMutex::new(BidirectionalTranslator::new(user_language, other_language)?));
+        let headphone_tap = Arc::new(TapBuffer::new(SWITCHBOARD_BUFFER_SIZE));
+        let virtual_mic_tap = Arc::new(TapBuffer::new(SWITCHBOARD_BUFFER_SIZE));
         
         Ok(AudioSwitchboard {
             outbound_pipeline: None,
             inbound_pipeline: None,
             physical_mic: None,
-            physical_headphones: None,
-            virtual_cable_input: None,
+            physical_headphones: Arc::new(std::sync::Mutex::new(OutputPath::new(Some(Arc::clone(&headphone_tap)), None))),
+            virtual_cable_input: Arc::new(std::sync::Mutex::new(OutputPath::new(
+                Some(Arc::clone(&virtual_mic_tap)),
+                Some(ComfortNoise::new(crate::SAMPLE_RATE)),
+            ))),
             virtual_cable_output: None,
             status: AudioSwitchboardStatus::Idle,
             control_tx: Some(control_tx),
             control_rx_clone: Arc::new(tokio::sync::Mutex::new(Some(control_rx))),
-            outbound_buffer: Arc::new(AsyncMutex::new(VecDeque::new())),
-            inbound_buffer: Arc::new(AsyncMutex::new(VecDeque::new())),
+            outbound_buffer: Arc::new(new_stream_buffer()),
+            inbound_buffer: Arc::new(new_stream_buffer()),
+            mic_tap: Arc::new(TapBuffer::new(SWITCHBOARD_BUFFER_SIZE)),
+            loopback_tap: Arc::new(TapBuffer::new(SWITCHBOARD_BUFFER_SIZE)),
+            virtual_mic_tap,
+            headphone_tap,
+            catch_up: CatchUpPolicy::default(),
+            virtual_mic_translation: AsyncMutex::new(()),
+            headphone_translation: AsyncMutex::new(()),
+            outbound_dsp: Arc::new(std::sync::Mutex::new(DspChain::voice_default(crate::SAMPLE_RATE))),
+            inbound_dsp: Arc::new(std::sync::Mutex::new(DspChain::voice_default(crate::SAMPLE_RATE))),
+            consumer_tasks: Vec::new(),
             translator,
         })
     }
@@ -88,10 +223,10 @@ impl AudioSwitchboard {
         self.physical_mic = Some(Box::new(crate::io::audio_device::MockAudioDevice::new()));
         
         // 初始化物理耳机
-        self.physical_headphones = Some(Box::new(crate::io::audio_device::MockAudioDevice::new()));
+        self.physical_headphones.lock().unwrap().device = Some(Box::new(crate::io::audio_device::MockAudioDevice::new()));
         
         // 初始化虚拟音频设备（模拟VB-Cable或BlackHole）
-        self.virtual_cable_input = Some(Box::new(crate::io::virtual_audio_device::VirtualAudioDevice::new(
+        self.virtual_cable_input.lock().unwrap().device = Some(Box::new(crate::io::virtual_audio_device::VirtualAudioDevice::new(
             "virtual_mic_input", 
             "virtual_mic_output", 
             16000, 
@@ -131,31 +266,53 @@ impl AudioSwitchboard {
 
     /// 设置发送端流水线：物理麦克风 -> 虚拟麦克风
     async fn setup_outbound_pipeline(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // 在打开任何流之前检查格式，避免格式不一致的音频悄悄进入流水线
+        if let Some(ref mic) = self.physical_mic {
+            self.translator.lock().await
+                .connect_outbound_source(mic.input_format())
+                .map_err(|e| format!("Physical mic: {}", e))?;
+        }
+        if let Some(ref virtual_mic) = self.virtual_cable_input.lock().unwrap().device {
+            AudioFormat::PIPELINE
+                .check_compatible(&virtual_mic.output_format())
+                .map_err(|e| format!("Virtual mic: {}", e))?;
+        }
+
+        self.outbound_buffer = Arc::new(new_stream_buffer());
         if let Some(ref mut mic) = self.physical_mic {
-            // 打开物理麦克风输入流
+            // 打开物理麦克风输入流，回调只负责把音频写入缓冲区
             mic.open_input_stream(Some("physical_mic".to_string()), Box::new({
-                let translator = Arc::clone(&self.translator);
-                
+                let buffer = Arc::clone(&self.outbound_buffer);
+                let tap = Arc::clone(&self.mic_tap);
                 move |audio_data| {
-                    // 1. 将物理麦克风音频数据发送到翻译器（发送方向：用户语言 -> 对方语言）
-                    let translator_clone = Arc::clone(&translator);
-                    let audio_vec = audio_data.to_vec();
-                    
-                    // 在tokio运行时中执行异步操作
-                    tokio::spawn(async move {
-                        // 处理发送端：物理麦克风声音 -> 翻译 -> 虚拟麦克风输出
-                        let _ = translator_clone.lock().await
-                            .handle_outbound_audio(&audio_vec)  // 从用户到对方
-                            .await;
-                    });
+                    buffer.write(audio_data);
+                    tap.write(audio_data);
                 }
             }))?;
             
             mic.start_recording()?;
         }
 
+        // 发送端翻译任务：按帧等待物理麦克风音频 -> 前端处理 -> 原声混入虚拟麦克风，同时送去翻译
+        let mut frames = self.outbound_buffer.frames(AudioFormat::PIPELINE);
+        let translator = Arc::clone(&self.translator);
+        let dsp = Arc::clone(&self.outbound_dsp);
+        let output = Arc::clone(&self.virtual_cable_input);
+        self.consumer_tasks.push(tokio::spawn(async move {
+            while let Some(mut frame) = frames.next().await {
+                output.lock().unwrap().measure_background(&frame.samples);
+                dsp.lock().unwrap().process_audio(&mut frame.samples);
+                if let Err(e) = output.lock().unwrap().mix_original(&frame.samples) {
+                    eprintln!("Virtual mic output error: {}", e);
+                }
+                translator.lock().await
+                    .handle_outbound_frame(&frame)  // 从用户到对方，保留帧的采样时钟
+                    .await;
+            }
+        }));
+
         // 设置虚拟麦克风输出（会议软件将从此获取音频）
-        if let Some(ref mut virtual_mic) = self.virtual_cable_input {
+        if let Some(ref mut virtual_mic) = self.virtual_cable_input.lock().unwrap().device {
             virtual_mic.open_output_stream(Some("virtual_mic_output".to_string()))?;
         }
 
@@ -165,30 +322,51 @@ impl AudioSwitchboard {
 
     /// 设置接收端流水线：系统环回 -> 物理耳机
     async fn setup_inbound_pipeline(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(ref virtual_spk) = self.virtual_cable_output {
+            self.translator.lock().await
+                .connect_inbound_source(virtual_spk.input_format())
+                .map_err(|e| format!("System loopback: {}", e))?;
+        }
+        if let Some(ref headphones) = self.physical_headphones.lock().unwrap().device {
+            AudioFormat::PIPELINE
+                .check_compatible(&headphones.output_format())
+                .map_err(|e| format!("Physical headphones: {}", e))?;
+        }
+
         // 模拟系统环回音频捕获（实际实现中需要从系统音频输出捕获）
+        self.inbound_buffer = Arc::new(new_stream_buffer());
         if let Some(ref mut virtual_spk) = self.virtual_cable_output {
             virtual_spk.open_input_stream(Some("virtual_spk_input".to_string()), Box::new({
-                let translator = Arc::clone(&self.translator);
-                
+                let buffer = Arc::clone(&self.inbound_buffer);
+                let tap = Arc::clone(&self.loopback_tap);
                 move |audio_data| {
-                    // 1. 将系统环回音频数据发送到翻译器（接收方向：对方语言 -> 用户语言）
-                    let translator_clone = Arc::clone(&translator);
-                    let audio_vec = audio_data.to_vec();
-                    
-                    tokio::spawn(async move {
-                        // 处理接收端：系统环回声音 -> 翻译 -> 物理耳机播放
-                        let _ = translator_clone.lock().await
-                            .handle_inbound_audio(&audio_vec)  // 从对方到用户
-                            .await;
-                    });
+                    buffer.write(audio_data);
+                    tap.write(audio_data);
                 }
             }))?;
             
             virtual_spk.start_recording()?;
         }
 
+        // 接收端翻译任务：按帧等待系统环回音频 -> 原声混入物理耳机 -> 前端处理 -> 翻译
+        let mut frames = self.inbound_buffer.frames(AudioFormat::PIPELINE);
+        let translator = Arc::clone(&self.translator);
+        let dsp = Arc::clone(&self.inbound_dsp);
+        let output = Arc::clone(&self.physical_headphones);
+        self.consumer_tasks.push(tokio::spawn(async move {
+            while let Some(mut frame) = frames.next().await {
+                if let Err(e) = output.lock().unwrap().mix_original(&frame.samples) {
+                    eprintln!("Headphone output error: {}", e);
+                }
+                dsp.lock().unwrap().process_audio(&mut frame.samples);
+                translator.lock().await
+                    .handle_inbound_frame(&frame)  // 从对方到用户，保留帧的采样时钟
+                    .await;
+            }
+        }));
+
         // 设置物理耳机输出
-        if let Some(ref mut headphones) = self.physical_headphones {
+        if let Some(ref mut headphones) = self.physical_headphones.lock().unwrap().device {
             headphones.open_output_stream(Some("physical_headphones".to_string()))?;
         }
 
@@ -243,11 +421,11 @@ impl AudioSwitchboard {
             mic.close_input_stream()?;
         }
         
-        if let Some(ref mut headphones) = self.physical_headphones {
+        if let Some(ref mut headphones) = self.physical_headphones.lock().unwrap().device {
             headphones.close_output_stream()?;
         }
         
-        if let Some(ref mut virtual_mic) = self.virtual_cable_input {
+        if let Some(ref mut virtual_mic) = self.virtual_cable_input.lock().unwrap().device {
             virtual_mic.stop_recording()?;
             virtual_mic.close_input_stream()?;
             virtual_mic.close_output_stream()?;
@@ -258,10 +436,212 @@ impl AudioSwitchboard {
             virtual_spk.close_input_stream()?;
         }
 
+        // 关闭缓冲区后翻译任务处理完剩余音频即退出
+        self.outbound_buffer.close();
+        self.inbound_buffer.close();
+        self.consumer_tasks.clear();
+
         println!("Audio switchboard stopped");
         Ok(())
     }
 
+    /// 接入物理麦克风分接读者（可在运行时随时接入，drop后自动分离）
+    pub fn tap_physical_mic(&self, policy: LagPolicy) -> TapReader<AudioSample> {
+        self.mic_tap.attach(policy)
+    }
+
+    /// 在指定音频流上接入电平表，每累计 `update_ms` 的音频发布一次读数
+    ///
+    /// 电平表在后台任务中读取分接，不影响音频路径；丢弃接收端后任务自动结束并分离分接。
+    pub fn attach_meter(&self, point: MeterPoint, config: LevelMeterConfig) -> tokio::sync::watch::Receiver<LevelReading> {
+        let tap = match point {
+            MeterPoint::PhysicalMic => &self.mic_tap,
+            MeterPoint::SystemLoopback => &self.loopback_tap,
+            MeterPoint::VirtualMic => &self.virtual_mic_tap,
+            MeterPoint::Headphones => &self.headphone_tap,
+        };
+        let mut reader = tap.attach(LagPolicy::SkipForward);
+        let mut meter = LevelMeter::with_config(crate::SAMPLE_RATE, config);
+        let (tx, rx) = tokio::sync::watch::channel(LevelReading::default());
+
+        tokio::spawn(async move {
+            let interval = meter.interval_samples().min(SWITCHBOARD_BUFFER_SIZE);
+            loop {
+                let audio = tokio::select! {
+                    _ = tx.closed() => return,
+                    audio = reader.read_exact(interval) => audio,
+                };
+                match audio {
+                    Ok(audio) => {
+                        for reading in meter.push_audio(&audio) {
+                            if tx.send(reading).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(TapError::Lagged { .. }) => continue,  // 电平表落后时跳过，只关心最新的音频
+                    Err(_) => return,
+                }
+            }
+        });
+        rx
+    }
+
+    /// 替换指定方向的前端处理链（运行中也可以替换，下一帧生效）
+    pub fn set_dsp_chain(&self, direction: TranslationDirection, chain: DspChain) {
+        *self.dsp_chain(direction).lock().unwrap() = chain;
+    }
+
+    /// 指定方向前端处理链的各级名称
+    pub fn dsp_stage_names(&self, direction: TranslationDirection) -> Vec<String> {
+        self.dsp_chain(direction).lock().unwrap().stage_names().into_iter().map(String::from).collect()
+    }
+
+    /// 指定方向自动增益控制的当前增益（dB），处理链中没有AGC时返回 None
+    pub fn agc_gain_db(&self, direction: TranslationDirection) -> Option<f32> {
+        self.dsp_chain(direction).lock().unwrap().stage_gain_db("agc")
+    }
+
+    /// 为指定方向的输入（物理麦克风或系统回环）启用或关闭降噪
+    ///
+    /// 降噪级插在去直流/高通之后、AGC之前，避免AGC先把底噪放大。
+    pub fn set_noise_suppression(&self, direction: TranslationDirection, enabled: bool) {
+        let mut chain = self.dsp_chain(direction).lock().unwrap();
+        let present = chain.position("noise_suppression").is_some();
+        if enabled && !present {
+            let index = ["high_pass", "dc_removal"]
+                .iter()
+                .find_map(|name| chain.position(name))
+                .map_or(0, |index| index + 1);
+            chain.insert(index, Box::new(NoiseSuppressor::new(crate::SAMPLE_RATE)));
+        } else if !enabled && present {
+            chain.remove("noise_suppression");
+        }
+    }
+
+    /// 指定方向的输入是否启用了降噪
+    pub fn noise_suppression_enabled(&self, direction: TranslationDirection) -> bool {
+        self.dsp_chain(direction).lock().unwrap().position("noise_suppression").is_some()
+    }
+
+    /// 启用或关闭发送端回声消除
+    ///
+    /// 以物理耳机的输出为参考，从物理麦克风信号中去掉外放漏进来的翻译语音，
+    /// 避免它在送入 `handle_outbound_frame` 之后被再次翻译。回声消除级放在处理链最前面，
+    /// 之后的AGC等非线性处理不会影响回声路径的估计。
+    pub fn set_echo_cancellation(&self, enabled: bool) {
+        let mut chain = self.outbound_dsp.lock().unwrap();
+        let present = chain.position("echo_cancellation").is_some();
+        if enabled && !present {
+            let reference = self.headphone_tap.attach(LagPolicy::SkipForward);
+            chain.insert(0, Box::new(EchoCanceller::new(crate::SAMPLE_RATE, reference)));
+        } else if !enabled && present {
+            chain.remove("echo_cancellation");
+        }
+    }
+
+    /// 按配置替换两个方向翻译流水线的VAD（之前订阅的VAD事件流需要重新订阅）
+    pub async fn set_vad_config(&self, vad_config: &VadConfig) -> Result<(), Box<dyn std::error::Error>> {
+        self.translator.lock().await.set_vad_config(vad_config)
+    }
+
+    /// 发送端是否启用了回声消除
+    pub fn echo_cancellation_enabled(&self) -> bool {
+        self.outbound_dsp.lock().unwrap().position("echo_cancellation").is_some()
+    }
+
+    /// 向物理耳机直接播放音频（不经过混音），同时作为回声消除的参考信号
+    pub fn play_to_headphones(&self, audio_data: &[AudioSample]) -> Result<usize, Box<dyn std::error::Error>> {
+        self.physical_headphones.lock().unwrap().play(audio_data)
+    }
+
+    /// 输出路径中尚未播放的音频时长（设备播放队列 + 排队等待混音的译文）
+    pub fn output_backlog(&self, direction: TranslationDirection) -> Duration {
+        self.output_path(direction).lock().unwrap().backlog()
+    }
+
+    /// 设置译文语音的追赶策略
+    pub fn set_catch_up_policy(&mut self, policy: CatchUpPolicy) {
+        self.catch_up = policy;
+    }
+
+    /// 设置输出路径的闪避混音参数（清空排队的译文）
+    pub fn set_ducking(&self, direction: TranslationDirection, config: DuckingConfig) {
+        self.output_path(direction).lock().unwrap().mixer = DuckingMixer::with_config(crate::SAMPLE_RATE, config);
+    }
+
+    /// 输出路径的闪避混音参数
+    pub fn ducking(&self, direction: TranslationDirection) -> DuckingConfig {
+        self.output_path(direction).lock().unwrap().mixer.config().clone()
+    }
+
+    /// 当前原声的闪避增益（dB）
+    pub fn current_duck_db(&self, direction: TranslationDirection) -> f32 {
+        self.output_path(direction).lock().unwrap().mixer.current_duck_db()
+    }
+
+    /// 把一段译文语音排入输出路径，与原声混音播放（`UserToOther` 进虚拟麦克风，`OtherToUser` 进物理耳机）
+    ///
+    /// 输出积压过多时先变速不变调地加快，返回使用的倍率。
+    ///
+    /// 目前只是外部调用的入口：翻译器只产出文本，交换机内部不合成语音，也不会自动调用这里，
+    /// 调用方需要自行用TTS合成译文后送入。变速追赶、闪避混音和TTS输出的响度归一化都只对这样送入的语音生效。
+    pub async fn play_translation(&self, direction: TranslationDirection, speech: &mut TtsResult) -> Result<f32, Box<dyn std::error::Error>> {
+        AudioFormat::PIPELINE
+            .check_compatible(&speech.format)
+            .map_err(|e| format!("Translated speech: {}", e))?;
+
+        // 同一路径的并发调用依次进行，否则都会按同一个过时的积压计算倍率
+        let _queueing = match direction {
+            TranslationDirection::UserToOther => self.virtual_mic_translation.lock().await,
+            TranslationDirection::OtherToUser => self.headphone_translation.lock().await,
+        };
+        // 变速耗时与语音长度成正比，不能持有输出路径的锁，否则会阻塞逐帧混音
+        let output = self.output_path(direction);
+        let backlog = output.lock().unwrap().backlog();
+        let rate = self.catch_up.rate_for(backlog, speech.duration());
+        if rate > 1.0 {
+            // 放到阻塞线程池中变速，不占用异步运行时的工作线程
+            let mut stretched = speech.clone();
+            *speech = tokio::task::spawn_blocking(move || {
+                stretched.time_stretch(rate);
+                stretched
+            }).await?;
+        }
+        output.lock().unwrap().mixer.push_translation_audio(&speech.audio_data);
+        Ok(rate)
+    }
+
+    /// 设置虚拟麦克风的舒适噪声，`None` 表示关闭（静音时输出数字静音）
+    pub fn set_comfort_noise(&self, config: Option<ComfortNoiseConfig>) {
+        self.virtual_cable_input.lock().unwrap().comfort_noise =
+            config.map(|config| ComfortNoise::with_config(crate::SAMPLE_RATE, config));
+    }
+
+    /// 虚拟麦克风的舒适噪声参数
+    pub fn comfort_noise(&self) -> Option<ComfortNoiseConfig> {
+        self.virtual_cable_input.lock().unwrap().comfort_noise.as_ref().map(|comfort| comfort.config().clone())
+    }
+
+    /// 虚拟麦克风舒适噪声的当前电平（dBFS），按测得的用户背景噪声计算
+    pub fn comfort_noise_level_db(&self) -> Option<f32> {
+        self.virtual_cable_input.lock().unwrap().comfort_noise.as_ref().map(|comfort| comfort.level_db())
+    }
+
+    fn output_path(&self, direction: TranslationDirection) -> &Arc<std::sync::Mutex<OutputPath>> {
+        match direction {
+            TranslationDirection::UserToOther => &self.virtual_cable_input,
+            TranslationDirection::OtherToUser => &self.physical_headphones,
+        }
+    }
+
+    fn dsp_chain(&self, direction: TranslationDirection) -> &Arc<std::sync::Mutex<DspChain>> {
+        match direction {
+            TranslationDirection::UserToOther => &self.outbound_dsp,
+            TranslationDirection::OtherToUser => &self.inbound_dsp,
+        }
+    }
+
     /// 获取当前状态
     pub fn get_status(&self) -> AudioSwitchboardStatus {
         self.status.clone()
@@ -277,9 +657,13 @@ impl AudioSwitchboard {
 
     /// 模拟物理麦克风输入（用于测试）
     pub async fn simulate_physical_mic_input(&self, audio_data: &[AudioSample]) {
-        // 将音频数据传递给发送端翻译流水线
+        // 与设备回调路径一致：分接原始音频，经前端处理链后混入虚拟麦克风并传递给发送端翻译流水线
+        self.mic_tap.write(audio_data);
+        self.virtual_cable_input.lock().unwrap().measure_background(audio_data);
         let translator_clone = Arc::clone(&self.translator);
-        let audio_vec = audio_data.to_vec();
+        let mut audio_vec = audio_data.to_vec();
+        self.outbound_dsp.lock().unwrap().process_audio(&mut audio_vec);
+        let _ = self.virtual_cable_input.lock().unwrap().mix_original(&audio_vec);
         
         let _ = translator_clone.lock().await
             .handle_outbound_audio(&audio_vec)
@@ -288,9 +672,12 @@ impl AudioSwitchboard {
 
     /// 模拟系统环回输入（用于测试）
     pub async fn simulate_system_loopback_input(&self, audio_data: &[AudioSample]) {
-        // 将音频数据传递给接收端翻译流水线
+        // 分接原始音频，原声混入物理耳机，经前端处理链后传递给接收端翻译流水线
+        self.loopback_tap.write(audio_data);
+        let _ = self.physical_headphones.lock().unwrap().mix_original(audio_data);
         let translator_clone = Arc::clone(&self.translator);
-        let audio_vec = audio_data.to_vec();
+        let mut audio_vec = audio_data.to_vec();
+        self.inbound_dsp.lock().unwrap().process_audio(&mut audio_vec);
         
         let _ = translator_clone.lock().await
             .handle_inbound_audio(&audio_vec)
@@ -298,6 +685,11 @@ impl AudioSwitchboard {
     }
 }
 
+/// 创建设备回调与翻译任务之间的缓冲区，翻译跟不上时丢弃最旧的音频以保持实时
+fn new_stream_buffer() -> RingBuffer<AudioSample> {
+    RingBuffer::with_policy(SWITCHBOARD_BUFFER_SIZE, OverflowPolicy::OverwriteOldest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -324,4 +716,251 @@ mod tests {
         // 测试停止
         assert!(switchboard.stop().is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_format_mismatch_rejected_at_connection() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+
+        // 系统环回设备为48kHz立体声，与16kHz单声道流水线不一致
+        switchboard.virtual_cable_output = Some(Box::new(crate::io::virtual_audio_device::VirtualAudioDevice::new(
+            "virtual_spk_input",
+            "virtual_spk_output",
+            48000,
+            2
+        )));
+
+        let err = switchboard.setup_inbound_pipeline().await.unwrap_err();
+        assert!(err.to_string().contains("System loopback"));
+        assert!(!switchboard.virtual_cable_output.as_ref().unwrap().is_recording());
+    }
+
+    #[tokio::test]
+    async fn test_physical_mic_taps() {
+        let switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        let mut meter = switchboard.tap_physical_mic(LagPolicy::SkipForward);
+        let mut recorder = switchboard.tap_physical_mic(LagPolicy::Drop);
+
+        switchboard.simulate_physical_mic_input(&[100; 320]).await;
+
+        // 每个分接读者独立读到完整的麦克风音频
+        assert_eq!(meter.read(1000).unwrap().len(), 320);
+        assert_eq!(recorder.read(1000).unwrap().len(), 320);
+        assert!(meter.read(1000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dsp_chain_per_direction() {
+        use crate::dsp::filters::Gain;
+
+        let switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        assert_eq!(
+            switchboard.dsp_stage_names(TranslationDirection::UserToOther),
+            vec!["dc_removal", "high_pass", "agc", "limiter"]
+        );
+        assert_eq!(switchboard.agc_gain_db(TranslationDirection::UserToOther), Some(0.0));
+
+        // 降噪按输入单独启用
+        switchboard.set_noise_suppression(TranslationDirection::UserToOther, true);
+        switchboard.set_noise_suppression(TranslationDirection::UserToOther, true);
+        assert_eq!(
+            switchboard.dsp_stage_names(TranslationDirection::UserToOther),
+            vec!["dc_removal", "high_pass", "noise_suppression", "agc", "limiter"]
+        );
+        assert!(!switchboard.noise_suppression_enabled(TranslationDirection::OtherToUser));
+        switchboard.set_noise_suppression(TranslationDirection::UserToOther, false);
+        assert!(!switchboard.noise_suppression_enabled(TranslationDirection::UserToOther));
+
+        switchboard.set_dsp_chain(TranslationDirection::OtherToUser, DspChain::new().with(Gain::from_db(6.0)));
+        assert_eq!(switchboard.dsp_stage_names(TranslationDirection::OtherToUser), vec!["gain"]);
+        assert_eq!(switchboard.agc_gain_db(TranslationDirection::OtherToUser), None);
+    }
+
+    #[tokio::test]
+    async fn test_echo_cancellation_uses_headphone_reference() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+
+        switchboard.set_echo_cancellation(true);
+        assert!(switchboard.echo_cancellation_enabled());
+        assert_eq!(switchboard.dsp_stage_names(TranslationDirection::UserToOther)[0], "echo_cancellation");
+        assert_eq!(switchboard.headphone_tap.reader_count(), 1);
+
+        // 耳机播放的音频进入参考分接，麦克风音频经过回声消除后送入翻译流水线
+        assert_eq!(switchboard.play_to_headphones(&[1000; 320]).unwrap(), 320);
+        assert_eq!(switchboard.headphone_tap.write_position(), 320);
+        switchboard.simulate_physical_mic_input(&[100; 320]).await;
+
+        switchboard.set_echo_cancellation(false);
+        assert!(!switchboard.echo_cancellation_enabled());
+        assert_eq!(switchboard.headphone_tap.reader_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_translation_speeds_up_with_backlog() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+        switchboard.set_catch_up_policy(CatchUpPolicy::default());
+        let speech = |seconds: usize| TtsResult {
+            text: "translated".to_string(),
+            audio_data: (0..16000 * seconds).map(|i| ((i as f32 * 0.08).sin() * 8000.0) as i16).collect(),
+            format: AudioFormat::PIPELINE,
+            success: true,
+            timestamp: std::time::Instant::now(),
+        };
+
+        // 队列为空时原速播放
+        let mut first = speech(2);
+        assert_eq!(switchboard.play_translation(TranslationDirection::OtherToUser, &mut first).await.unwrap(), 1.0);
+        assert_eq!(first.duration(), Duration::from_secs(2));
+
+        // 积压约2秒：下一段加速并缩短
+        let backlog = switchboard.output_backlog(TranslationDirection::OtherToUser);
+        assert!(backlog > Duration::from_millis(1900) && backlog <= Duration::from_secs(2));
+        let mut second = speech(2);
+        let rate = switchboard.play_translation(TranslationDirection::OtherToUser, &mut second).await.unwrap();
+        assert_eq!(rate, 1.5);
+        assert!(second.duration() < Duration::from_millis(1400));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_translations_see_each_others_backlog() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+        switchboard.set_catch_up_policy(CatchUpPolicy { target_backlog_ms: 500, max_rate: 4.0 });
+        let speech = |seconds: usize| TtsResult {
+            text: "translated".to_string(),
+            audio_data: (0..16000 * seconds).map(|i| ((i as f32 * 0.08).sin() * 8000.0) as i16).collect(),
+            format: AudioFormat::PIPELINE,
+            success: true,
+            timestamp: std::time::Instant::now(),
+        };
+        switchboard.play_translation(TranslationDirection::OtherToUser, &mut speech(1)).await.unwrap();
+
+        // 两段译文同时排入同一路径，都需要变速：后入队的一段必须按前一段加上的积压计算倍率
+        let (mut first, mut second) = (speech(4), speech(4));
+        let (first, second) = tokio::join!(
+            switchboard.play_translation(TranslationDirection::OtherToUser, &mut first),
+            switchboard.play_translation(TranslationDirection::OtherToUser, &mut second),
+        );
+        let mut rates = [first.unwrap(), second.unwrap()];
+        rates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert!(rates[0] < 1.2 && rates[1] > 2.0, "{:?}", rates);
+    }
+
+    #[tokio::test]
+    async fn test_translation_mixed_over_ducked_original() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+        let config = DuckingConfig { duck_db: -20.0, translation_fade_ms: 0.0, ..Default::default() };
+        switchboard.set_ducking(TranslationDirection::OtherToUser, config.clone());
+        assert_eq!(switchboard.ducking(TranslationDirection::OtherToUser), config);
+
+        let mut speech = TtsResult {
+            text: "translated".to_string(),
+            audio_data: vec![2000; 16000],
+            format: AudioFormat::PIPELINE,
+            success: true,
+            timestamp: std::time::Instant::now(),
+        };
+        switchboard.play_translation(TranslationDirection::OtherToUser, &mut speech).await.unwrap();
+
+        // 对方原声经过系统环回进入耳机：压低20dB后垫在译文下面
+        let mut heard = switchboard.headphone_tap.attach(LagPolicy::Drop);
+        for _ in 0..25 {
+            switchboard.simulate_system_loopback_input(&[10000; 320]).await;
+        }
+        let mixed = heard.read(8000).unwrap();
+        assert_eq!(mixed.len(), 8000);
+        assert!((mixed[7999] as i32 - 3000).abs() <= 2, "{}", mixed[7999]);
+        assert!((switchboard.current_duck_db(TranslationDirection::OtherToUser) + 20.0).abs() < 0.1);
+
+        // 虚拟麦克风一侧没有译文，原声不受影响
+        assert_eq!(switchboard.current_duck_db(TranslationDirection::UserToOther), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_level_meters_on_streams() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+        let mut mic = switchboard.attach_meter(MeterPoint::PhysicalMic, LevelMeterConfig::default());
+        let headphones = switchboard.attach_meter(MeterPoint::Headphones, LevelMeterConfig::default());
+        assert_eq!(switchboard.mic_tap.reader_count(), 1);
+
+        // 100ms 半幅音频产生一个读数
+        for _ in 0..5 {
+            switchboard.simulate_physical_mic_input(&[16384; 320]).await;
+        }
+        tokio::time::timeout(Duration::from_secs(1), mic.changed()).await.unwrap().unwrap();
+        let reading = mic.borrow().clone();
+        assert!((reading.peak_db + 6.02).abs() < 0.1);
+        assert_eq!(reading.sample_position, 1600);
+        assert_eq!(reading.clip_count, 0);
+        assert!(!headphones.has_changed().unwrap());
+
+        // 丢弃接收端后电平表任务分离分接
+        drop(mic);
+        drop(headphones);
+        for _ in 0..50 {
+            if switchboard.mic_tap.reader_count() == 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(switchboard.mic_tap.reader_count(), 0);
+        assert_eq!(switchboard.headphone_tap.reader_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_comfort_noise_fills_virtual_mic_silence() {
+        let mut switchboard = AudioSwitchboard::new("zh", "en").unwrap();
+        switchboard.initialize_devices().unwrap();
+        assert_eq!(switchboard.comfort_noise(), Some(ComfortNoiseConfig::default()));
+        // 模拟输入比实时快，积压不代表真实播放延迟，不做追赶
+        switchboard.set_catch_up_policy(CatchUpPolicy { target_backlog_ms: 60_000, ..Default::default() });
+        let ducking = DuckingConfig { translation_fade_ms: 0.0, ..Default::default() };
+        switchboard.set_ducking(TranslationDirection::UserToOther, ducking);
+        let mut virtual_mic = switchboard.virtual_mic_tap.attach(LagPolicy::Drop);
+        let rms_db = |audio: &[AudioSample]| {
+            crate::dsp::filters::linear_to_db(
+                (audio.iter().map(|&s| (s.to_f32()) * s.to_f32()).sum::<f32>() / audio.len() as f32).sqrt())
+        };
+
+        // 用户环境的背景噪声约 -50 dBFS
+        let mut ditherer = Ditherer::with_seed(7);
+        for _ in 0..50 {
+            let noise: Vec<AudioSample> = (0..320).map(|i| ditherer.quantize(((i * 7919 % 320) as f32 / 320.0 - 0.5) * 0.011)).collect();
+            switchboard.simulate_physical_mic_input(&noise).await;
+        }
+        let level = switchboard.comfort_noise_level_db().unwrap();
+        assert!(level > -62.0 && level < -50.0, "{}", level);
+        virtual_mic.read(16000).unwrap();
+
+        // 麦克风静音：虚拟麦克风输出舒适噪声而不是数字静音
+        for _ in 0..25 {
+            switchboard.simulate_physical_mic_input(&[0; 320]).await;
+        }
+        let filled = virtual_mic.read(8000).unwrap();
+        assert!((rms_db(&filled[4000..]) - level).abs() < 2.0, "{} vs {}", rms_db(&filled[4000..]), level);
+
+        // 译文到达的那一帧起只输出译文
+        let mut speech = TtsResult {
+            text: "translated".to_string(),
+            audio_data: vec![2000; 3200],
+            format: AudioFormat::PIPELINE,
+            success: true,
+            timestamp: std::time::Instant::now(),
+        };
+        switchboard.play_translation(TranslationDirection::UserToOther, &mut speech).await.unwrap();
+        switchboard.simulate_physical_mic_input(&[0; 320]).await;
+        let translated = virtual_mic.read(320).unwrap();
+        assert!(translated.iter().all(|&s| (s - 2000).abs() <= 2));
+
+        // 关闭后静音帧保持静音
+        switchboard.set_comfort_noise(None);
+        assert_eq!(switchboard.comfort_noise_level_db(), None);
+        switchboard.set_ducking(TranslationDirection::UserToOther, DuckingConfig::default());
+        switchboard.simulate_physical_mic_input(&[0; 320]).await;
+        assert!(virtual_mic.read(320).unwrap().iter().all(|&s| s.abs() <= 2));
+    }
+}
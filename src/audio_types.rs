@@ -8,24 +8,194 @@ pub const FRAME_SIZE_MS: u32 = 20;         // 帧大小：20ms
 pub const SAMPLES_PER_FRAME: usize = (SAMPLE_RATE as usize * FRAME_SIZE_MS as usize) / 1000;  // 每帧样本数
 
 // 音频数据类型别名
-pub type AudioSample = i16;                // 音频采样点类型
+pub type AudioSample = i16;                // 流水线内部交换的采样点类型，与设备格式的转换见 core::sample
 
 // 音频缓冲区大小
 pub const DEFAULT_RING_BUFFER_SIZE: usize = 8192;   // 默认环形缓冲区大小
 
+/// 音频格式描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AudioFormat {
+    pub sample_rate: u32,    // 采样率（Hz）
+    pub channels: u16,       // 声道数
+    pub frame_size_ms: u32,  // 帧时长（毫秒）
+}
+
+impl AudioFormat {
+    /// 流水线默认格式：16kHz、单声道、20ms帧
+    pub const PIPELINE: AudioFormat = AudioFormat::new(SAMPLE_RATE, CHANNELS, FRAME_SIZE_MS);
+
+    /// 创建新的音频格式
+    pub const fn new(sample_rate: u32, channels: u16, frame_size_ms: u32) -> Self {
+        AudioFormat {
+            sample_rate,
+            channels,
+            frame_size_ms,
+        }
+    }
+
+    /// 返回帧时长不同的同一格式
+    pub const fn with_frame_size(self, frame_size_ms: u32) -> Self {
+        AudioFormat::new(self.sample_rate, self.channels, frame_size_ms)
+    }
+
+    /// 每帧的交错采样总数
+    pub fn samples_per_frame(&self) -> usize {
+        self.samples_for_ms(self.frame_size_ms)
+    }
+
+    /// 指定毫秒数对应的交错采样数
+    pub fn samples_for_ms(&self, duration_ms: u32) -> usize {
+        (self.sample_rate as usize * duration_ms as usize) / 1000 * self.channels as usize
+    }
+
+    /// 指定交错采样数对应的时长
+    pub fn duration_of(&self, sample_count: usize) -> std::time::Duration {
+        let frames = sample_count / self.channels.max(1) as usize;
+        std::time::Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// 检查另一端的采样率与声道数是否与本格式一致（帧时长可以不同）
+    pub fn check_compatible(&self, other: &AudioFormat) -> Result<(), FormatMismatch> {
+        if self.sample_rate != other.sample_rate || self.channels != other.channels {
+            return Err(FormatMismatch {
+                expected: *self,
+                actual: *other,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for AudioFormat {
+    fn default() -> Self {
+        AudioFormat::PIPELINE
+    }
+}
+
+impl std::fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Hz, {} ch, {} ms", self.sample_rate, self.channels, self.frame_size_ms)
+    }
+}
+
+/// 音频格式不匹配错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatMismatch {
+    pub expected: AudioFormat,
+    pub actual: AudioFormat,
+}
+
+impl std::fmt::Display for FormatMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Audio format mismatch: expected {}, got {}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for FormatMismatch {}
+
+/// 带格式信息的音频缓冲区
 #[derive(Debug, Clone)]
-pub struct AudioFrame {
-    pub samples: [AudioSample; SAMPLES_PER_FRAME],
+pub struct AudioBuffer {
+    pub format: AudioFormat,
+    pub samples: Vec<AudioSample>,  // 交错排列的采样
     pub timestamp: std::time::Instant,
 }
 
+impl AudioBuffer {
+    /// 创建新的音频缓冲区
+    pub fn new(format: AudioFormat, samples: Vec<AudioSample>) -> Self {
+        AudioBuffer {
+            format,
+            samples,
+            timestamp: std::time::Instant::now(),
+        }
+    }
+
+    /// 创建指定时长的静音缓冲区
+    pub fn silence(format: AudioFormat, duration_ms: u32) -> Self {
+        AudioBuffer::new(format, vec![0; format.samples_for_ms(duration_ms)])
+    }
+
+    /// 每声道的采样帧数
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.format.channels.max(1) as usize
+    }
+
+    /// 缓冲区时长
+    pub fn duration(&self) -> std::time::Duration {
+        self.format.duration_of(self.samples.len())
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// 音频帧，长度由格式中的帧时长决定
+///
+/// `sample_index` 是该帧第一个采样在整条流中的位置（按每声道采样帧计），
+/// 时间戳由流起始时间加上采样偏移得出，而不是处理时的墙上时钟。
+#[derive(Debug, Clone)]
+pub struct AudioFrame {
+    pub format: AudioFormat,
+    pub samples: Vec<AudioSample>,
+    pub sample_index: u64,                  // 首个采样在流中的序号
+    pub stream_start: std::time::Instant,   // 流起始时间
+    pub timestamp: std::time::Instant,      // 首个采样对应的时间
+}
+
 impl AudioFrame {
+    /// 创建默认格式的静音帧
     pub fn new() -> Self {
+        AudioFrame::with_format(AudioFormat::PIPELINE)
+    }
+
+    /// 创建指定格式的静音帧
+    pub fn with_format(format: AudioFormat) -> Self {
+        AudioFrame::at(format, vec![0; format.samples_per_frame()], std::time::Instant::now(), 0)
+    }
+
+    /// 创建位于流中指定位置的帧
+    pub fn at(
+        format: AudioFormat,
+        samples: Vec<AudioSample>,
+        stream_start: std::time::Instant,
+        sample_index: u64,
+    ) -> Self {
         AudioFrame {
-            samples: [0; SAMPLES_PER_FRAME],
-            timestamp: std::time::Instant::now(),
+            format,
+            samples,
+            sample_index,
+            stream_start,
+            timestamp: stream_start + sample_offset(sample_index, format.sample_rate),
         }
     }
+
+    /// 帧起点相对于流起始的偏移
+    pub fn offset(&self) -> std::time::Duration {
+        sample_offset(self.sample_index, self.format.sample_rate)
+    }
+
+    /// 紧接本帧之后的采样序号
+    pub fn end_sample_index(&self) -> u64 {
+        self.sample_index + (self.samples.len() / self.format.channels.max(1) as usize) as u64
+    }
+}
+
+/// 采样序号换算为时间偏移（整数运算，长时间运行不累积误差）
+pub fn sample_offset(sample_index: u64, sample_rate: u32) -> std::time::Duration {
+    let rate = sample_rate.max(1) as u64;
+    let secs = sample_index / rate;
+    let nanos = (sample_index % rate) * 1_000_000_000 / rate;
+    std::time::Duration::new(secs, nanos as u32)
+}
+
+impl Default for AudioFrame {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -40,4 +210,22 @@ mod tests {
         assert_eq!(FRAME_SIZE_MS, 20);
         assert_eq!(SAMPLES_PER_FRAME, 320); // 16000 * 20 / 1000 = 320
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_audio_format() {
+        let telephony = AudioFormat::new(8000, 1, 20);
+        assert_eq!(telephony.samples_per_frame(), 160);
+        assert_eq!(AudioFormat::new(48000, 2, 10).samples_per_frame(), 960);
+        assert_eq!(AudioFormat::PIPELINE.samples_per_frame(), SAMPLES_PER_FRAME);
+        assert_eq!(AudioFrame::with_format(telephony).samples.len(), 160);
+
+        // 帧时长不同的同一格式是兼容的，采样率或声道不同则不兼容
+        assert!(AudioFormat::PIPELINE.check_compatible(&AudioFormat::PIPELINE.with_frame_size(30)).is_ok());
+        let err = AudioFormat::PIPELINE.check_compatible(&telephony).unwrap_err();
+        assert_eq!(err.actual, telephony);
+
+        let buffer = AudioBuffer::silence(AudioFormat::new(48000, 2, 20), 100);
+        assert_eq!(buffer.frame_count(), 4800);
+        assert_eq!(buffer.duration(), std::time::Duration::from_millis(100));
+    }
+}
@@ -7,8 +7,10 @@ use std::time::Instant;
 use tokio::sync::oneshot;
 use crate::{
     engine::translation_pipeline::{TranslationPipeline, TranslationResult, TranslationCallback},
+    engine::vad::{VadConfig, VadEventStream},
+    core::tap_buffer::LagPolicy,
     io::audio_capture::AudioCapture,
-    AudioSample, SAMPLES_PER_FRAME
+    AudioFormat, AudioFrame, AudioSample, FormatMismatch, SAMPLES_PER_FRAME
 };
 
 /// 语言对结构
@@ -153,12 +155,12 @@ impl BidirectionalTranslator {
                 TranslationDirection::UserToOther => {
                     // 用户说话，转换为目标语言
                     let mut pipeline = user_to_other_pipeline.lock().unwrap();
-                    pipeline.process_audio(audio_data);
+                    pipeline.process_stream(audio_data);
                 }
                 TranslationDirection::OtherToUser => {
                     // 对方说话，转换为用户语言
                     let mut pipeline = other_to_user_pipeline.lock().unwrap();
-                    pipeline.process_audio(audio_data);
+                    pipeline.process_stream(audio_data);
                 }
             }
         }));
@@ -231,6 +233,34 @@ impl BidirectionalTranslator {
         self.current_pair.lock().unwrap().clone()
     }
 
+    /// 检查发送端音频源格式（用户 -> 对方）
+    pub fn connect_outbound_source(&self, format: AudioFormat) -> Result<(), FormatMismatch> {
+        self.user_to_other_pipeline.lock().unwrap().connect_input(format)
+    }
+
+    /// 检查接收端音频源格式（对方 -> 用户）
+    pub fn connect_inbound_source(&self, format: AudioFormat) -> Result<(), FormatMismatch> {
+        self.other_to_user_pipeline.lock().unwrap().connect_input(format)
+    }
+
+    /// 按配置替换两个方向流水线的VAD
+    ///
+    /// 新VAD从静音状态开始，之前订阅的VAD事件流不再收到事件，需要重新订阅。
+    pub fn set_vad_config(&self, vad_config: &VadConfig) -> Result<(), Box<dyn std::error::Error>> {
+        self.user_to_other_pipeline.lock().unwrap().set_vad_config(vad_config)?;
+        self.other_to_user_pipeline.lock().unwrap().set_vad_config(vad_config)
+    }
+
+    /// 订阅发送端（用户 -> 对方）的VAD事件流
+    pub fn outbound_vad_events(&self, policy: LagPolicy) -> VadEventStream {
+        self.user_to_other_pipeline.lock().unwrap().vad_events(policy)
+    }
+
+    /// 订阅接收端（对方 -> 用户）的VAD事件流
+    pub fn inbound_vad_events(&self, policy: LagPolicy) -> VadEventStream {
+        self.other_to_user_pipeline.lock().unwrap().vad_events(policy)
+    }
+
     /// 处理传入的音频数据（例如，从虚拟音频设备接收）
     pub fn handle_incoming_audio(&self, audio_data: &[AudioSample], is_user_speaking: bool) {
         if !self.running.load(std::sync::atomic::Ordering::SeqCst) {
@@ -241,11 +271,11 @@ impl BidirectionalTranslator {
         if is_user_speaking {
             // 用户说话，转换为目标语言
             let mut pipeline = self.user_to_other_pipeline.lock().unwrap();
-            pipeline.process_audio(audio_data);
+            pipeline.process_stream(audio_data);
         } else {
             // 对方说话，转换为用户语言
             let mut pipeline = self.other_to_user_pipeline.lock().unwrap();
-            pipeline.process_audio(audio_data);
+            pipeline.process_stream(audio_data);
         }
     }
 
@@ -259,7 +289,7 @@ impl BidirectionalTranslator {
 
         // 通过用户到对方的翻译流水线处理音频
         let mut pipeline = self.user_to_other_pipeline.lock().unwrap();
-        pipeline.process_audio(audio_data);
+        pipeline.process_stream(audio_data);
     }
 
     /// 处理接收端音频（对方说话，翻译成用户语言）
@@ -272,7 +302,19 @@ impl BidirectionalTranslator {
 
         // 通过对方到用户的翻译流水线处理音频
         let mut pipeline = self.other_to_user_pipeline.lock().unwrap();
-        pipeline.process_audio(audio_data);
+        pipeline.process_stream(audio_data);
+    }
+
+    /// 处理发送端已带采样时钟的音频帧（用户说话，翻译成对方语言）
+    pub async fn handle_outbound_frame(&self, frame: &AudioFrame) {
+        *self.current_direction.lock().unwrap() = TranslationDirection::UserToOther;
+        self.user_to_other_pipeline.lock().unwrap().process_audio_frame(frame);
+    }
+
+    /// 处理接收端已带采样时钟的音频帧（对方说话，翻译成用户语言）
+    pub async fn handle_inbound_frame(&self, frame: &AudioFrame) {
+        *self.current_direction.lock().unwrap() = TranslationDirection::OtherToUser;
+        self.other_to_user_pipeline.lock().unwrap().process_audio_frame(frame);
     }
 
     /// 模拟用户说话
@@ -352,4 +394,39 @@ mod tests {
         assert_eq!(updated_pair.source, "en");
         assert_eq!(updated_pair.target, "fr");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_outbound_audio_reaches_vad_events() {
+        use tokio_stream::StreamExt;
+        use crate::engine::vad::VadEvent;
+
+        let mut translator = BidirectionalTranslator::new("zh", "en").unwrap();
+        translator.start().unwrap();
+        let mut events = translator.outbound_vad_events(LagPolicy::SkipForward);
+
+        // 发送端音频经过VAD，订阅者收到按采样位置对齐的语音开始事件
+        translator.handle_outbound_audio(&vec![0; 4800]).await;
+        translator.handle_outbound_audio(&vec![3000; 4800]).await;
+        assert_eq!(events.next().await, Some(Ok(VadEvent::SpeechStart { sample_offset: 4800 })));
+    }
+
+    #[tokio::test]
+    async fn test_vad_config_reaches_both_pipelines() {
+        use tokio_stream::StreamExt;
+        use crate::engine::vad::VadEvent;
+
+        let mut translator = BidirectionalTranslator::new("zh", "en").unwrap();
+        translator.set_vad_config(&VadConfig { frame_ms: 20, ..VadConfig::default() }).unwrap();
+        translator.start().unwrap();
+        let mut outbound = translator.outbound_vad_events(LagPolicy::SkipForward);
+        let mut inbound = translator.inbound_vad_events(LagPolicy::SkipForward);
+
+        // 20ms帧：语音开始落在第13帧的起点（默认30ms帧时为4320）
+        translator.handle_outbound_audio(&vec![0; 4400]).await;
+        translator.handle_outbound_audio(&vec![3000; 4800]).await;
+        translator.handle_inbound_audio(&vec![0; 4400]).await;
+        translator.handle_inbound_audio(&vec![3000; 4800]).await;
+        assert_eq!(outbound.next().await, Some(Ok(VadEvent::SpeechStart { sample_offset: 4160 })));
+        assert_eq!(inbound.next().await, Some(Ok(VadEvent::SpeechStart { sample_offset: 4160 })));
+    }
+}
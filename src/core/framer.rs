@@ -0,0 +1,192 @@
+//! 分帧模块
+//! 将设备回调交付的任意长度缓冲区整理为定长音频帧，并按采样时钟打时间戳
+
+use std::time::Instant;
+use crate::audio_types::{AudioFormat, AudioFrame, AudioSample};
+
+/// 分帧器
+///
+/// 累积输入直到凑满一帧再输出，不足一帧的尾部保留到下一次调用。
+/// 每帧携带单调递增的采样序号，时间戳 = 流起始时间 + 采样偏移，
+/// 因此下游的语音边界、词语时间和字幕时间都与采样精确对齐。
+pub struct Framer {
+    format: AudioFormat,
+    frame_len: usize,               // 每帧的交错采样数
+    pending: Vec<AudioSample>,      // 尚未凑满一帧的输入
+    next_index: u64,                // 下一帧首个采样的序号
+    stream_start: Instant,
+}
+
+impl Framer {
+    /// 创建新的分帧器，流起始时间取当前时间
+    pub fn new(format: AudioFormat) -> Self {
+        Self::with_start(format, Instant::now())
+    }
+
+    /// 使用指定的流起始时间创建分帧器
+    pub fn with_start(format: AudioFormat, stream_start: Instant) -> Self {
+        let frame_len = format.samples_per_frame().max(1);
+        Framer {
+            format,
+            frame_len,
+            pending: Vec::with_capacity(frame_len),
+            next_index: 0,
+            stream_start,
+        }
+    }
+
+    /// 输出帧的格式
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    /// 流起始时间
+    pub fn stream_start(&self) -> Instant {
+        self.stream_start
+    }
+
+    /// 下一帧首个采样的序号
+    pub fn next_sample_index(&self) -> u64 {
+        self.next_index
+    }
+
+    /// 缓存中尚未输出的采样数
+    pub fn pending_samples(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 推入一段输入，返回凑满的全部帧
+    pub fn push(&mut self, input: &[AudioSample]) -> Vec<AudioFrame> {
+        let mut frames = Vec::with_capacity((self.pending.len() + input.len()) / self.frame_len);
+        self.push_with(input, |frame| frames.push(frame));
+        frames
+    }
+
+    /// 推入一段输入，每凑满一帧调用一次 `on_frame`
+    pub fn push_with<F: FnMut(AudioFrame)>(&mut self, mut input: &[AudioSample], mut on_frame: F) {
+        // 先补齐上次剩下的半帧
+        if !self.pending.is_empty() {
+            let needed = self.frame_len - self.pending.len();
+            let take = needed.min(input.len());
+            self.pending.extend_from_slice(&input[..take]);
+            input = &input[take..];
+            if self.pending.len() < self.frame_len {
+                return;
+            }
+            let samples = std::mem::replace(&mut self.pending, Vec::with_capacity(self.frame_len));
+            on_frame(self.emit(samples));
+        }
+
+        let mut chunks = input.chunks_exact(self.frame_len);
+        for chunk in &mut chunks {
+            on_frame(self.emit(chunk.to_vec()));
+        }
+        self.pending.extend_from_slice(chunks.remainder());
+    }
+
+    /// 推入上游已带采样时钟的帧（帧长可以不同），输出帧沿用上游的采样序号与流起始时间
+    ///
+    /// 上游序号与缓存不连续（丢帧或流重启）时丢弃缓存中的半帧，从上游序号重新对齐。
+    pub fn push_frame(&mut self, frame: &AudioFrame) -> Vec<AudioFrame> {
+        let channels = self.format.channels.max(1) as u64;
+        let expected = self.next_index + self.pending.len() as u64 / channels;
+        if frame.sample_index != expected || frame.stream_start != self.stream_start {
+            self.pending.clear();
+            self.next_index = frame.sample_index;
+            self.stream_start = frame.stream_start;
+        }
+        self.push(&frame.samples)
+    }
+
+    /// 输出剩余的不完整帧（用静音补齐），没有剩余时返回 None
+    pub fn flush(&mut self) -> Option<AudioFrame> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let mut samples = std::mem::take(&mut self.pending);
+        samples.resize(self.frame_len, 0);
+        Some(self.emit(samples))
+    }
+
+    /// 丢弃缓存并从新的起始时间重新计数
+    pub fn reset(&mut self, stream_start: Instant) {
+        self.pending.clear();
+        self.next_index = 0;
+        self.stream_start = stream_start;
+    }
+
+    fn emit(&mut self, samples: Vec<AudioSample>) -> AudioFrame {
+        let frame = AudioFrame::at(self.format, samples, self.stream_start, self.next_index);
+        self.next_index = frame.end_sample_index();
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_framer_emits_exact_frames() {
+        let format = AudioFormat::new(16000, 1, 20);
+        let mut framer = Framer::new(format);
+        let input: Vec<AudioSample> = (0..1000).map(|i| i as AudioSample).collect();
+
+        // 模拟不规则的设备回调大小
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        for size in [1usize, 255, 64, 680].iter() {
+            frames.extend(framer.push(&input[offset..offset + size]));
+            offset += size;
+        }
+
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|f| f.samples.len() == 320));
+        // 帧之间首尾相接，内容未被打乱
+        for (n, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.sample_index, n as u64 * 320);
+            assert_eq!(frame.samples[0], (n * 320) as AudioSample);
+        }
+        assert_eq!(framer.pending_samples(), 40);
+
+        let tail = framer.flush().unwrap();
+        assert_eq!(tail.samples.len(), 320);
+        assert_eq!(tail.samples[39], 999);
+        assert_eq!(tail.samples[40], 0);
+        assert!(framer.flush().is_none());
+    }
+
+    #[test]
+    fn test_framer_sample_clock_timestamps() {
+        let start = Instant::now();
+        let format = AudioFormat::new(48000, 2, 10);
+        let mut framer = Framer::with_start(format, start);
+
+        // 一小时的立体声音频，时间戳只由采样数决定
+        let block = vec![0; format.samples_per_frame() * 100];
+        let mut last = None;
+        for _ in 0..3600 {
+            framer.push_with(&block, |frame| last = Some(frame));
+        }
+
+        let last = last.unwrap();
+        assert_eq!(last.samples.len(), 960);
+        assert_eq!(last.sample_index, 48000 * 3600 - 480);
+        assert_eq!(last.offset(), Duration::from_secs(3600) - Duration::from_millis(10));
+        assert_eq!(last.timestamp, start + last.offset());
+        assert_eq!(framer.next_sample_index(), 48000 * 3600);
+
+        // 上游20ms帧重新分为30ms帧，沿用上游的采样时钟；丢帧后从上游序号重新对齐
+        let upstream = AudioFormat::new(16000, 1, 20);
+        let mut framer = Framer::new(AudioFormat::new(16000, 1, 30));
+        let frames: Vec<AudioFrame> = [0u64, 320, 640, 1600, 1920]
+            .iter()
+            .flat_map(|&index| framer.push_frame(&AudioFrame::at(upstream, vec![0; 320], start, index)))
+            .collect();
+        let indices: Vec<u64> = frames.iter().map(|f| f.sample_index).collect();
+        assert_eq!(indices, vec![0, 480, 1600]);
+        assert_eq!(frames[2].timestamp, start + Duration::from_millis(100));
+        assert_eq!(framer.pending_samples(), 160);
+    }
+}
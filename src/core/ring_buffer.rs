@@ -1,70 +1,307 @@
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio_stream::Stream;
+use crate::audio_types::{AudioFormat, AudioFrame, AudioSample};
+
+/// 缓冲区写满时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    #[default]
+    RejectNew,        // 丢弃放不下的新数据
+    OverwriteOldest,  // 丢弃最旧的数据，为新数据腾出空间
+    Block(Duration),  // 等待消费者腾出空间，超时后丢弃放不下的新数据
+}
+
+/// 缓冲区运行统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RingBufferStats {
+    pub overruns: u64,         // 写入时空间不足的次数
+    pub underruns: u64,        // 读取时数据不足的次数
+    pub dropped_samples: u64,  // 因溢出而丢弃的采样数（新数据或被覆盖的旧数据）
+    pub written_samples: u64,  // 成功写入的采样数
+    pub read_samples: u64,     // 成功读取的采样数
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    stats: RingBufferStats,
+}
 
 /// 高性能环形缓冲区，用于处理实时音频流
 /// 
 /// 该结构实现了线程安全的环形缓冲区，支持生产者-消费者模式，
-/// 专为实时音频数据处理设计。
+/// 专为实时音频数据处理设计。写满时的行为由 [`OverflowPolicy`] 决定，
+/// 溢出和欠载次数记录在 [`RingBufferStats`] 中，用于判断流水线是否跟不上实时。
 pub struct RingBuffer<T> {
-    buffer: Arc<Mutex<VecDeque<T>>>,
+    state: Mutex<State<T>>,
+    not_full: Condvar,
+    data_available: Notify,  // 唤醒等待数据的异步读取者
+    closed: AtomicBool,
     capacity: usize,
+    policy: Mutex<OverflowPolicy>,
 }
 
 impl<T: Clone> RingBuffer<T> {
     /// 创建一个新的环形缓冲区
     pub fn new(capacity: usize) -> Self {
+        Self::with_policy(capacity, OverflowPolicy::default())
+    }
+
+    /// 使用指定的溢出策略创建环形缓冲区
+    pub fn with_policy(capacity: usize, policy: OverflowPolicy) -> Self {
         RingBuffer {
-            buffer: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            state: Mutex::new(State {
+                queue: VecDeque::with_capacity(capacity),
+                stats: RingBufferStats::default(),
+            }),
+            not_full: Condvar::new(),
+            data_available: Notify::new(),
+            closed: AtomicBool::new(false),
             capacity,
+            policy: Mutex::new(policy),
         }
     }
 
-    /// 写入数据到缓冲区
+    /// 当前溢出策略
+    pub fn policy(&self) -> OverflowPolicy {
+        *self.policy.lock().unwrap()
+    }
+
+    /// 修改溢出策略
+    pub fn set_policy(&self, policy: OverflowPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    /// 写入数据到缓冲区，返回写入的元素数
     pub fn write(&self, data: &[T]) -> usize {
-        let mut buffer = self.buffer.lock().unwrap();
-        let writable = self.capacity - buffer.len();
-        let to_write = std::cmp::min(data.len(), writable);
-        
-        for item in data.iter().take(to_write) {
-            buffer.push_back(item.clone());
+        let policy = self.policy();
+        let mut state = self.state.lock().unwrap();
+
+        if let OverflowPolicy::Block(timeout) = policy {
+            // 只等待不超过容量的部分，超过容量的数据永远不可能一次放下
+            let needed = data.len().min(self.capacity);
+            let deadline = Instant::now() + timeout;
+            while self.capacity - state.queue.len() < needed {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                state = self.not_full.wait_timeout(state, deadline - now).unwrap().0;
+            }
         }
-        
-        // 如果缓冲区满了，移除旧数据
-        while buffer.len() > self.capacity {
-            buffer.pop_front();
+
+        let writable = self.capacity - state.queue.len();
+        if data.len() > writable {
+            state.stats.overruns += 1;
+        }
+
+        let to_write = match policy {
+            OverflowPolicy::OverwriteOldest => {
+                // 新数据本身超过容量时只保留最新的部分
+                let skipped = data.len().saturating_sub(self.capacity);
+                let data = &data[skipped..];
+                let evicted = (state.queue.len() + data.len()).saturating_sub(self.capacity);
+                state.queue.drain(..evicted);
+                state.queue.extend(data.iter().cloned());
+                state.stats.dropped_samples += (skipped + evicted) as u64;
+                data.len()
+            }
+            OverflowPolicy::RejectNew | OverflowPolicy::Block(_) => {
+                let to_write = std::cmp::min(data.len(), writable);
+                state.queue.extend(data[..to_write].iter().cloned());
+                state.stats.dropped_samples += (data.len() - to_write) as u64;
+                to_write
+            }
+        };
+
+        state.stats.written_samples += to_write as u64;
+        drop(state);
+
+        if to_write > 0 {
+            self.data_available.notify_waiters();
         }
-        
         to_write
     }
 
     /// 从缓冲区读取数据
     pub fn read(&self, size: usize) -> Vec<T> {
-        let mut buffer = self.buffer.lock().unwrap();
-        let readable = std::cmp::min(size, buffer.len());
-        let mut result = Vec::with_capacity(readable);
-        
-        for _ in 0..readable {
-            if let Some(item) = buffer.pop_front() {
-                result.push(item);
-            }
+        let mut state = self.state.lock().unwrap();
+        let readable = std::cmp::min(size, state.queue.len());
+        if readable < size {
+            state.stats.underruns += 1;
+        }
+
+        let result: Vec<T> = state.queue.drain(..readable).collect();
+        state.stats.read_samples += readable as u64;
+        drop(state);
+
+        if readable > 0 {
+            self.not_full.notify_all();
         }
-        
         result
     }
 
     /// 获取缓冲区中可读数据的大小
     pub fn readable_size(&self) -> usize {
-        self.buffer.lock().unwrap().len()
+        self.state.lock().unwrap().queue.len()
     }
 
     /// 获取缓冲区中可写空间的大小
     pub fn writable_size(&self) -> usize {
-        self.capacity - self.buffer.lock().unwrap().len()
+        self.capacity - self.state.lock().unwrap().queue.len()
+    }
+
+    /// 缓冲区容量
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 获取运行统计
+    pub fn stats(&self) -> RingBufferStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// 清零运行统计
+    pub fn reset_stats(&self) {
+        self.state.lock().unwrap().stats = RingBufferStats::default();
     }
 
     /// 清空缓冲区
     pub fn clear(&self) {
-        self.buffer.lock().unwrap().clear();
+        self.state.lock().unwrap().queue.clear();
+        self.not_full.notify_all();
+    }
+
+    /// 关闭缓冲区：唤醒所有等待中的异步读取者，之后的等待立即返回剩余数据
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.data_available.notify_waiters();
+    }
+
+    /// 缓冲区是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// 异步读取恰好 `size` 个元素，数据不足时等待生产者写入
+    ///
+    /// `size` 超过容量时按容量读取；缓冲区关闭后返回剩余的全部数据（可能少于 `size`）。
+    pub async fn read_exact(&self, size: usize) -> Vec<T> {
+        let size = size.min(self.capacity);
+        self.wait_for(size, None).await;
+        self.take(size)
+    }
+
+    /// 异步读取至少 `size` 个元素，返回当前可读的全部数据
+    ///
+    /// 超时或缓冲区关闭时返回已有的数据（可能少于 `size`，也可能为空）。
+    pub async fn read_at_least(&self, size: usize, timeout: Duration) -> Vec<T> {
+        self.wait_for(size.min(self.capacity), Some(Instant::now() + timeout)).await;
+        self.take(usize::MAX)
+    }
+
+    /// 等待可读数据达到 `size`，或到达截止时间、缓冲区关闭
+    async fn wait_for(&self, size: usize, deadline: Option<Instant>) {
+        loop {
+            // 先注册再检查条件，避免检查之后、等待之前的写入通知丢失
+            let notified = self.data_available.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.readable_size() >= size || self.is_closed() {
+                return;
+            }
+
+            match deadline {
+                Some(deadline) => {
+                    let deadline = tokio::time::Instant::from_std(deadline);
+                    if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                        return;
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// 读取最多 `size` 个元素（异步读取使用，不计入欠载统计）
+    fn take(&self, size: usize) -> Vec<T> {
+        let mut state = self.state.lock().unwrap();
+        let readable = std::cmp::min(size, state.queue.len());
+        let result: Vec<T> = state.queue.drain(..readable).collect();
+        state.stats.read_samples += readable as u64;
+        drop(state);
+
+        if readable > 0 {
+            self.not_full.notify_all();
+        }
+        result
+    }
+}
+
+impl RingBuffer<AudioSample> {
+    /// 将缓冲区转换为音频帧流，每帧长度由格式决定，时间戳按采样时钟计算
+    pub fn frames(self: &Arc<Self>, format: AudioFormat) -> AudioFrameStream {
+        AudioFrameStream {
+            buffer: Arc::clone(self),
+            format,
+            stream_start: Instant::now(),
+            next_index: 0,
+            pending: None,
+        }
+    }
+}
+
+type PendingRead = Pin<Box<dyn Future<Output = Vec<AudioSample>> + Send>>;
+
+/// 由环形缓冲区驱动的音频帧流，生产者写入时被唤醒
+///
+/// 缓冲区关闭后，剩余不足一帧的数据以静音补齐输出，然后流结束。
+pub struct AudioFrameStream {
+    buffer: Arc<RingBuffer<AudioSample>>,
+    format: AudioFormat,
+    stream_start: Instant,
+    next_index: u64,
+    pending: Option<PendingRead>,
+}
+
+impl AudioFrameStream {
+    /// 输出帧的格式
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+}
+
+impl Stream for AudioFrameStream {
+    type Item = AudioFrame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<AudioFrame>> {
+        let frame_len = self.format.samples_per_frame();
+        if self.pending.is_none() {
+            let buffer = Arc::clone(&self.buffer);
+            self.pending = Some(Box::pin(async move { buffer.read_exact(frame_len).await }));
+        }
+
+        let mut samples = match self.pending.as_mut().unwrap().as_mut().poll(cx) {
+            Poll::Ready(samples) => samples,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.pending = None;
+
+        if samples.is_empty() {
+            return Poll::Ready(None);
+        }
+        samples.resize(frame_len, 0);
+
+        let frame = AudioFrame::at(self.format, samples, self.stream_start, self.next_index);
+        self.next_index = frame.end_sample_index();
+        Poll::Ready(Some(frame))
     }
 }
 
@@ -96,4 +333,103 @@ mod tests {
         assert_eq!(written, 8); // 只能写入8个，因为可写空间只有8
         assert_eq!(rb.readable_size(), 10); // 应该是满的（2个旧的+8个新的）
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_overflow_policies() {
+        // 丢弃新数据：保留旧数据，统计被丢弃的新数据
+        let rb = RingBuffer::with_policy(4, OverflowPolicy::RejectNew);
+        assert_eq!(rb.write(&[1, 2, 3]), 3);
+        assert_eq!(rb.write(&[4, 5, 6]), 1);
+        assert_eq!(rb.read(4), vec![1, 2, 3, 4]);
+        assert_eq!(rb.read(1), Vec::<i32>::new());
+        let stats = rb.stats();
+        assert_eq!((stats.overruns, stats.underruns, stats.dropped_samples), (1, 1, 2));
+
+        // 覆盖旧数据：保留最新的数据
+        let rb = RingBuffer::with_policy(4, OverflowPolicy::OverwriteOldest);
+        rb.write(&[1, 2, 3]);
+        assert_eq!(rb.write(&[4, 5, 6]), 3);
+        assert_eq!(rb.read(4), vec![3, 4, 5, 6]);
+        assert_eq!(rb.write(&[7, 8, 9, 10, 11, 12]), 4);
+        assert_eq!(rb.read(4), vec![9, 10, 11, 12]);
+        assert_eq!(rb.stats().dropped_samples, 4);
+        assert_eq!(rb.stats().overruns, 2);
+    }
+
+    #[test]
+    fn test_block_policy_waits_for_consumer() {
+        let rb = std::sync::Arc::new(RingBuffer::with_policy(4, OverflowPolicy::Block(Duration::from_secs(5))));
+        rb.write(&[1, 2, 3, 4]);
+
+        let reader = {
+            let rb = std::sync::Arc::clone(&rb);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                rb.read(2)
+            })
+        };
+
+        // 阻塞直到消费者读走两个元素，新数据不丢失
+        assert_eq!(rb.write(&[5, 6]), 2);
+        assert_eq!(reader.join().unwrap(), vec![1, 2]);
+        assert_eq!(rb.read(4), vec![3, 4, 5, 6]);
+        assert_eq!(rb.stats().dropped_samples, 0);
+
+        // 超时后丢弃放不下的部分
+        rb.set_policy(OverflowPolicy::Block(Duration::from_millis(10)));
+        rb.write(&[1, 2, 3]);
+        assert_eq!(rb.write(&[4, 5]), 1);
+        assert_eq!(rb.stats().dropped_samples, 1);
+    }
+
+    #[tokio::test]
+    async fn test_async_reads_woken_by_producer() {
+        let rb = Arc::new(RingBuffer::<i16>::new(64));
+
+        let producer = {
+            let rb = Arc::clone(&rb);
+            tokio::spawn(async move {
+                for chunk in [[1, 2, 3], [4, 5, 6]] {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    rb.write(&chunk);
+                }
+            })
+        };
+
+        assert_eq!(rb.read_exact(5).await, vec![1, 2, 3, 4, 5]);
+        producer.await.unwrap();
+
+        // 数据不足时等到超时，返回已有的部分
+        assert_eq!(rb.read_at_least(4, Duration::from_millis(10)).await, vec![6]);
+        assert_eq!(rb.stats().underruns, 0);
+
+        // 超过容量的请求按容量读取，不会永远等不满
+        rb.write(&[9; 64]);
+        assert_eq!(rb.read_exact(100).await, vec![9; 64]);
+    }
+
+    #[tokio::test]
+    async fn test_audio_frame_stream() {
+        use tokio_stream::StreamExt;
+
+        let rb = Arc::new(RingBuffer::<AudioSample>::new(4096));
+        let mut frames = rb.frames(AudioFormat::new(16000, 1, 20));
+
+        rb.write(&vec![1; 500]);
+        rb.write(&vec![2; 300]);
+        rb.close();
+
+        let first = frames.next().await.unwrap();
+        let second = frames.next().await.unwrap();
+        let tail = frames.next().await.unwrap();
+        assert!(frames.next().await.is_none());
+
+        assert_eq!(first.samples.len(), 320);
+        assert_eq!(second.sample_index, 320);
+        assert_eq!(second.samples[180], 2);
+        // 关闭后剩余的160个采样补齐为一帧
+        assert_eq!(tail.sample_index, 640);
+        assert_eq!(tail.samples[159], 2);
+        assert_eq!(tail.samples[160], 0);
+    }
+}
@@ -0,0 +1,61 @@
+//! 伪随机数发生器
+//! 抖动、舒适噪声等只需要快速、可复现噪声的场合共用的 xorshift32
+
+/// xorshift32 伪随机数发生器
+///
+/// 周期 2³²−1，不适合密码学用途；同一种子产生的序列固定，便于测试复现。
+#[derive(Debug, Clone)]
+pub struct XorShift32 {
+    state: u32,
+}
+
+impl XorShift32 {
+    /// 使用指定种子创建（种子为0时改用1，全零状态不会再变化）
+    pub fn new(seed: u32) -> Self {
+        XorShift32 { state: seed.max(1) }
+    }
+
+    /// 下一个32位随机数
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// [0, 1) 均匀分布随机数
+    pub fn next_uniform(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// [-1, 1) 均匀分布随机数
+    pub fn next_signed(&mut self) -> f32 {
+        self.next_uniform() * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 可复现的白噪声，幅度在 [-amplitude, amplitude) 内均匀分布
+    pub(crate) fn white_noise(count: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+        let mut rng = XorShift32::new(seed);
+        (0..count).map(|_| rng.next_signed() * amplitude).collect()
+    }
+
+    #[test]
+    fn test_uniform_range_and_mean() {
+        let mut rng = XorShift32::new(0);
+        let values: Vec<f32> = (0..100_000).map(|_| rng.next_uniform()).collect();
+        assert!(values.iter().all(|&v| (0.0..1.0).contains(&v)));
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        assert!((mean - 0.5).abs() < 0.01);
+
+        // 同一种子序列相同
+        assert_eq!(white_noise(16, 1.0, 7), white_noise(16, 1.0, 7));
+        assert_ne!(white_noise(16, 1.0, 7), white_noise(16, 1.0, 8));
+    }
+}
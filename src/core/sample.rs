@@ -0,0 +1,220 @@
+//! 采样类型抽象
+//! 统一 i16/i32/u16/f32 采样之间的转换，DSP和模型内部使用f32处理，输出时带抖动量化回整数
+
+use crate::core::rng::XorShift32;
+
+/// 音频采样类型
+///
+/// 整数类型之间通过32位满幅值中转，互相转换不丢失精度；
+/// 涉及浮点时通过 [-1.0, 1.0) 范围的 f32 中转。
+pub trait Sample: Copy + Send + Sync + 'static {
+    /// 静音值
+    const EQUILIBRIUM: Self;
+    /// 是否为浮点类型
+    const IS_FLOAT: bool;
+    /// 一个量化步长对应的浮点幅度（浮点类型为0）
+    const LSB: f32;
+
+    /// 转换为 [-1.0, 1.0) 范围的浮点数
+    fn to_f32(self) -> f32;
+
+    /// 从浮点数转换（四舍五入并限幅）
+    fn from_f32(value: f32) -> Self;
+
+    /// 转换为32位满幅整数
+    fn to_i32(self) -> i32;
+
+    /// 从32位满幅整数转换（四舍五入到目标精度）
+    fn from_i32(value: i32) -> Self;
+
+    /// 转换为另一种采样类型
+    fn convert<S: Sample>(self) -> S {
+        if Self::IS_FLOAT || S::IS_FLOAT {
+            S::from_f32(self.to_f32())
+        } else {
+            S::from_i32(self.to_i32())
+        }
+    }
+}
+
+impl Sample for i16 {
+    const EQUILIBRIUM: Self = 0;
+    const IS_FLOAT: bool = false;
+    const LSB: f32 = 1.0 / 32768.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / 32768.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value * 32768.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+
+    fn to_i32(self) -> i32 {
+        (self as i32) << 16
+    }
+
+    fn from_i32(value: i32) -> Self {
+        ((value as i64 + 0x8000) >> 16).clamp(i16::MIN as i64, i16::MAX as i64) as i16
+    }
+}
+
+impl Sample for u16 {
+    const EQUILIBRIUM: Self = 0x8000;
+    const IS_FLOAT: bool = false;
+    const LSB: f32 = 1.0 / 32768.0;
+
+    fn to_f32(self) -> f32 {
+        (self as f32 - 32768.0) / 32768.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value * 32768.0 + 32768.0).round().clamp(0.0, u16::MAX as f32) as u16
+    }
+
+    fn to_i32(self) -> i32 {
+        ((self as i32) - 0x8000) << 16
+    }
+
+    fn from_i32(value: i32) -> Self {
+        (i16::from_i32(value) as i32 + 0x8000) as u16
+    }
+}
+
+impl Sample for i32 {
+    const EQUILIBRIUM: Self = 0;
+    const IS_FLOAT: bool = false;
+    const LSB: f32 = 1.0 / 2_147_483_648.0;
+
+    fn to_f32(self) -> f32 {
+        (self as f64 / 2_147_483_648.0) as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value as f64 * 2_147_483_648.0).round().clamp(i32::MIN as f64, i32::MAX as f64) as i32
+    }
+
+    fn to_i32(self) -> i32 {
+        self
+    }
+
+    fn from_i32(value: i32) -> Self {
+        value
+    }
+}
+
+impl Sample for f32 {
+    const EQUILIBRIUM: Self = 0.0;
+    const IS_FLOAT: bool = true;
+    const LSB: f32 = 0.0;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn to_i32(self) -> i32 {
+        i32::from_f32(self)
+    }
+
+    fn from_i32(value: i32) -> Self {
+        value.to_f32()
+    }
+}
+
+/// 将任意采样转换为内部浮点处理格式
+pub fn to_float_buffer<S: Sample>(input: &[S]) -> Vec<f32> {
+    input.iter().map(|&s| s.to_f32()).collect()
+}
+
+/// 不带抖动地将浮点数据转换为目标采样类型
+pub fn from_float_buffer<S: Sample>(input: &[f32]) -> Vec<S> {
+    input.iter().map(|&s| S::from_f32(s)).collect()
+}
+
+/// TPDF（三角概率密度）抖动量化器
+///
+/// 在量化到整数前叠加 ±1 LSB 的三角分布噪声，把截断失真转化为
+/// 与信号无关的白噪声，避免低电平信号（淡入淡出、混响尾音）出现谐波失真。
+pub struct Ditherer {
+    rng: XorShift32,
+}
+
+impl Ditherer {
+    /// 创建新的抖动器
+    pub fn new() -> Self {
+        Self::with_seed(0x9E37_79B9)
+    }
+
+    /// 使用指定种子创建抖动器
+    pub fn with_seed(seed: u32) -> Self {
+        Ditherer { rng: XorShift32::new(seed) }
+    }
+
+    /// 量化单个浮点采样
+    pub fn quantize<S: Sample>(&mut self, value: f32) -> S {
+        if S::IS_FLOAT {
+            return S::from_f32(value);
+        }
+        let noise = (self.rng.next_uniform() - self.rng.next_uniform()) * S::LSB;
+        S::from_f32(value + noise)
+    }
+
+    /// 量化一段浮点数据并追加到 `output`
+    pub fn quantize_into<S: Sample>(&mut self, input: &[f32], output: &mut Vec<S>) {
+        output.extend(input.iter().map(|&s| self.quantize::<S>(s)));
+    }
+}
+
+impl Default for Ditherer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_conversions_are_lossless() {
+        for value in [i16::MIN, -12345, -1, 0, 1, 777, i16::MAX] {
+            assert_eq!(value.convert::<i32>().convert::<i16>(), value);
+            assert_eq!(value.convert::<u16>().convert::<i16>(), value);
+            assert_eq!(value.convert::<f32>().convert::<i16>(), value);
+        }
+        assert_eq!(0i16.convert::<u16>(), 0x8000);
+        assert_eq!(i16::MIN.convert::<u16>(), 0);
+        assert_eq!(i16::MAX.convert::<i32>(), 0x7FFF_0000);
+    }
+
+    #[test]
+    fn test_float_conversion_rounds_and_clips() {
+        assert_eq!(i16::from_f32(0.5), 16384);
+        assert_eq!(i16::from_f32(1.5), i16::MAX);
+        assert_eq!(i16::from_f32(-1.5), i16::MIN);
+        // 四舍五入而不是截断
+        assert_eq!(i16::from_f32(0.9 / 32768.0), 1);
+        assert_eq!(u16::from_f32(-1.0), 0);
+        assert_eq!(1.25f32.convert::<f32>(), 1.25);
+    }
+
+    #[test]
+    fn test_dither_decorrelates_quantization() {
+        // 0.3 LSB 的直流信号：不加抖动时全部量化为0，加抖动后平均值接近0.3 LSB
+        let value = 0.3 / 32768.0;
+        assert_eq!(i16::from_f32(value), 0);
+
+        let mut ditherer = Ditherer::new();
+        let count = 100_000;
+        let sum: i64 = (0..count).map(|_| ditherer.quantize::<i16>(value) as i64).sum();
+        let mean = sum as f64 / count as f64;
+        assert!((mean - 0.3).abs() < 0.02, "mean {}", mean);
+
+        // 浮点目标不加抖动
+        assert_eq!(ditherer.quantize::<f32>(0.25), 0.25);
+    }
+}
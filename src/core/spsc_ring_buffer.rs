@@ -0,0 +1,294 @@
+//! 无锁单生产者单消费者环形缓冲区
+//! 供音频设备回调使用：不加锁、构造后不再分配内存，写入和读取都是wait-free
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 独占一条缓存行的原子计数器，避免生产者和消费者互相使对方的缓存失效
+#[repr(align(64))]
+struct CachePadded(AtomicUsize);
+
+/// 生产者与消费者共享的存储
+///
+/// `head` 与 `tail` 是单调递增（回绕）的计数器，`tail - head` 即可读元素数。
+/// 容量是2的幂，计数器回绕到0时 `position & mask` 仍然连续，槽位不会跳变。
+/// 只有消费者写 `head`，只有生产者写 `tail`，因此一次 Acquire/Release 配对即可保证可见性。
+struct Shared<T> {
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    capacity: usize,
+    mask: usize,        // capacity - 1
+    head: CachePadded,  // 下一个读取位置（消费者拥有）
+    tail: CachePadded,  // 下一个写入位置（生产者拥有）
+}
+
+impl<T> Shared<T> {
+    fn slot(&self, position: usize) -> *mut T {
+        self.buffer[position & self.mask].get() as *mut T
+    }
+
+    fn base(&self) -> *mut T {
+        // UnsafeCell 与 MaybeUninit 都是 repr(transparent)，连续存储的布局与 [T] 相同
+        self.buffer.as_ptr() as *mut T
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let head = *self.head.0.get_mut();
+        let tail = *self.tail.0.get_mut();
+        let mut position = head;
+        while position != tail {
+            unsafe { ptr::drop_in_place(self.slot(position)) };
+            position = position.wrapping_add(1);
+        }
+    }
+}
+
+/// 无锁SPSC环形缓冲区的构造入口
+pub struct SpscRingBuffer;
+
+impl SpscRingBuffer {
+    /// 创建至少能容纳 `capacity` 个元素的缓冲区（向上取整到2的幂），返回分离的生产者和消费者句柄
+    pub fn with_capacity<T: Send>(capacity: usize) -> (RingProducer<T>, RingConsumer<T>) {
+        assert!(capacity > 0, "ring buffer capacity must be positive");
+        let capacity = capacity.checked_next_power_of_two().expect("ring buffer capacity too large");
+        let buffer = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let shared = Arc::new(Shared {
+            buffer,
+            capacity,
+            mask: capacity - 1,
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        });
+
+        (
+            RingProducer { shared: Arc::clone(&shared) },
+            RingConsumer { shared },
+        )
+    }
+}
+
+/// 生产者句柄（只能有一个，可以移动到另一个线程）
+pub struct RingProducer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// 消费者句柄（只能有一个，可以移动到另一个线程）
+pub struct RingConsumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+unsafe impl<T: Send> Send for RingProducer<T> {}
+unsafe impl<T: Send> Send for RingConsumer<T> {}
+
+impl<T> RingProducer<T> {
+    /// 缓冲区容量
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// 当前可读元素数
+    pub fn len(&self) -> usize {
+        let head = self.shared.head.0.load(Ordering::Acquire);
+        let tail = self.shared.tail.0.load(Ordering::Relaxed);
+        tail.wrapping_sub(head)
+    }
+
+    /// 缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前可写空间
+    pub fn free_len(&self) -> usize {
+        self.shared.capacity - self.len()
+    }
+
+    /// 写入单个元素，缓冲区已满时原样返回
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.free_len() == 0 {
+            return Err(value);
+        }
+        let tail = self.shared.tail.0.load(Ordering::Relaxed);
+        unsafe { ptr::write(self.shared.slot(tail), value) };
+        self.shared.tail.0.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+}
+
+impl<T: Copy> RingProducer<T> {
+    /// 批量写入，返回实际写入的元素数（空间不足时截断）
+    pub fn write(&mut self, data: &[T]) -> usize {
+        let count = data.len().min(self.free_len());
+        if count == 0 {
+            return 0;
+        }
+
+        let capacity = self.shared.capacity;
+        let tail = self.shared.tail.0.load(Ordering::Relaxed);
+        let start = tail & self.shared.mask;
+        let first = count.min(capacity - start);
+        unsafe {
+            let base = self.shared.base();
+            ptr::copy_nonoverlapping(data.as_ptr(), base.add(start), first);
+            ptr::copy_nonoverlapping(data.as_ptr().add(first), base, count - first);
+        }
+        self.shared.tail.0.store(tail.wrapping_add(count), Ordering::Release);
+        count
+    }
+}
+
+impl<T> RingConsumer<T> {
+    /// 缓冲区容量
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// 当前可读元素数
+    pub fn len(&self) -> usize {
+        let head = self.shared.head.0.load(Ordering::Relaxed);
+        let tail = self.shared.tail.0.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    /// 缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 读取单个元素
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let head = self.shared.head.0.load(Ordering::Relaxed);
+        let value = unsafe { ptr::read(self.shared.slot(head)) };
+        self.shared.head.0.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    /// 丢弃最多 `count` 个元素，返回实际丢弃的数量
+    pub fn skip(&mut self, count: usize) -> usize {
+        let count = count.min(self.len());
+        let head = self.shared.head.0.load(Ordering::Relaxed);
+        for offset in 0..count {
+            unsafe { ptr::drop_in_place(self.shared.slot(head.wrapping_add(offset))) };
+        }
+        self.shared.head.0.store(head.wrapping_add(count), Ordering::Release);
+        count
+    }
+}
+
+impl<T: Copy> RingConsumer<T> {
+    /// 批量读取到 `output`，返回实际读取的元素数
+    pub fn read(&mut self, output: &mut [T]) -> usize {
+        let count = output.len().min(self.len());
+        if count == 0 {
+            return 0;
+        }
+
+        let capacity = self.shared.capacity;
+        let head = self.shared.head.0.load(Ordering::Relaxed);
+        let start = head & self.shared.mask;
+        let first = count.min(capacity - start);
+        unsafe {
+            let base = self.shared.base();
+            ptr::copy_nonoverlapping(base.add(start), output.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(base, output.as_mut_ptr().add(first), count - first);
+        }
+        self.shared.head.0.store(head.wrapping_add(count), Ordering::Release);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spsc_wraparound() {
+        // 容量向上取整到2的幂
+        let (mut producer, mut consumer) = SpscRingBuffer::with_capacity::<i16>(10);
+        assert_eq!(producer.capacity(), 16);
+
+        assert_eq!(producer.write(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]), 13);
+        let mut out = [0i16; 11];
+        assert_eq!(consumer.read(&mut out), 11);
+        assert_eq!(out, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+
+        // 写入跨越存储末尾，且只能写入剩余的14个空位
+        let data: Vec<i16> = (20..40).collect();
+        assert_eq!(producer.write(&data), 14);
+        assert_eq!(producer.free_len(), 0);
+        assert_eq!(producer.push(99), Err(99));
+
+        let mut out = [0i16; 32];
+        assert_eq!(consumer.read(&mut out), 16);
+        assert_eq!(&out[..4], &[12, 13, 20, 21]);
+        assert_eq!(out[15], 33);
+        assert!(consumer.pop().is_none());
+
+        // 计数器从 usize::MAX 回绕到0时，读写位置仍然连续
+        let (mut producer, mut consumer) = SpscRingBuffer::with_capacity::<i16>(8);
+        producer.shared.head.0.store(usize::MAX - 2, Ordering::Relaxed);
+        producer.shared.tail.0.store(usize::MAX - 2, Ordering::Relaxed);
+        assert_eq!(producer.write(&[1, 2, 3, 4, 5, 6]), 6);
+        assert_eq!(consumer.len(), 6);
+        let mut out = [0i16; 6];
+        assert_eq!(consumer.read(&mut out), 6);
+        assert_eq!(out, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_spsc_across_threads() {
+        let (mut producer, mut consumer) = SpscRingBuffer::with_capacity::<u32>(64);
+        let total = 50_000u32;
+
+        let writer = std::thread::spawn(move || {
+            let mut next = 0u32;
+            while next < total {
+                let chunk: Vec<u32> = (next..(next + 37).min(total)).collect();
+                let written = producer.write(&chunk);
+                if written == 0 {
+                    std::thread::yield_now();
+                }
+                next += written as u32;
+            }
+        });
+
+        let mut expected = 0u32;
+        let mut out = [0u32; 29];
+        while expected < total {
+            let count = consumer.read(&mut out);
+            if count == 0 {
+                std::thread::yield_now();
+            }
+            for &value in &out[..count] {
+                assert_eq!(value, expected);
+                expected += 1;
+            }
+        }
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_spsc_drops_remaining_items() {
+        let marker = Arc::new(());
+        {
+            let (mut producer, mut consumer) = SpscRingBuffer::with_capacity(4);
+            for _ in 0..4 {
+                producer.push(Arc::clone(&marker)).unwrap();
+            }
+            drop(consumer.pop());
+            assert_eq!(consumer.skip(1), 1);
+            assert_eq!(Arc::strong_count(&marker), 3);
+        }
+        assert_eq!(Arc::strong_count(&marker), 1);
+    }
+}
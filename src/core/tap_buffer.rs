@@ -0,0 +1,317 @@
+//! 多读者分接缓冲区
+//! 一路音频同时供给VAD、ASR、电平表、录音等多个消费者，每个读者拥有独立的读取位置
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// 读者落后超过缓冲区容量时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    #[default]
+    SkipForward,  // 跳到最旧的可用数据继续读取，并报告跳过的采样数
+    Drop,         // 断开该读者
+}
+
+/// 分接读取错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapError {
+    Lagged { skipped: u64 },   // 读者落后，已跳过指定数量的采样
+    Detached { skipped: u64 }, // 读者因落后被断开（或已被分离）
+    TooLarge { requested: usize, capacity: usize }, // 请求的采样数超过缓冲区容量，永远无法满足
+}
+
+impl std::fmt::Display for TapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TapError::Lagged { skipped } => write!(f, "Tap reader lagged, skipped {} samples", skipped),
+            TapError::Detached { skipped } => write!(f, "Tap reader detached after lagging {} samples", skipped),
+            TapError::TooLarge { requested, capacity } => {
+                write!(f, "Tap read of {} samples exceeds buffer capacity {}", requested, capacity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TapError {}
+
+/// 单个读者的状态
+struct ReaderState {
+    cursor: u64,          // 下一个要读取的采样序号
+    policy: LagPolicy,
+    skipped: u64,         // 累计跳过的采样数
+    detached: bool,
+}
+
+struct State<T> {
+    data: VecDeque<T>,
+    write_pos: u64,       // 已写入的采样总数
+    next_id: u64,
+    readers: HashMap<u64, ReaderState>,
+}
+
+impl<T> State<T> {
+    /// 缓冲区中最旧采样的序号
+    fn oldest(&self) -> u64 {
+        self.write_pos - self.data.len() as u64
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+    data_available: Notify,
+}
+
+/// 多读者分接缓冲区
+///
+/// 写入方从不阻塞：缓冲区满时覆盖最旧的数据。每个读者按自己的游标读取，
+/// 落后超过容量的读者按其 [`LagPolicy`] 跳过或被断开。读者可以在运行时随时接入和分离。
+pub struct TapBuffer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> TapBuffer<T> {
+    /// 创建指定容量的分接缓冲区
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "tap buffer capacity must be positive");
+        TapBuffer {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    data: VecDeque::with_capacity(capacity),
+                    write_pos: 0,
+                    next_id: 0,
+                    readers: HashMap::new(),
+                }),
+                capacity,
+                data_available: Notify::new(),
+            }),
+        }
+    }
+
+    /// 缓冲区容量
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// 当前接入的读者数
+    pub fn reader_count(&self) -> usize {
+        let state = self.shared.state.lock().unwrap();
+        state.readers.values().filter(|reader| !reader.detached).count()
+    }
+
+    /// 已写入的采样总数
+    pub fn write_position(&self) -> u64 {
+        self.shared.state.lock().unwrap().write_pos
+    }
+
+    /// 写入数据，满时覆盖最旧的数据
+    pub fn write(&self, data: &[T]) {
+        if data.is_empty() {
+            return;
+        }
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            let capacity = self.shared.capacity;
+            let skipped = data.len().saturating_sub(capacity);
+            let evicted = (state.data.len() + data.len() - skipped).saturating_sub(capacity);
+            state.data.drain(..evicted);
+            state.data.extend(data[skipped..].iter().cloned());
+            state.write_pos += data.len() as u64;
+        }
+        self.shared.data_available.notify_waiters();
+    }
+
+    /// 接入新的读者，从当前写入位置开始读取
+    pub fn attach(&self, policy: LagPolicy) -> TapReader<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let cursor = state.write_pos;
+        state.readers.insert(id, ReaderState { cursor, policy, skipped: 0, detached: false });
+
+        TapReader {
+            shared: Arc::clone(&self.shared),
+            id,
+        }
+    }
+}
+
+/// 分接读者，分离（drop）后不再占用缓冲区
+pub struct TapReader<T> {
+    shared: Arc<Shared<T>>,
+    id: u64,
+}
+
+impl<T: Clone> TapReader<T> {
+    /// 读取最多 `max` 个采样
+    ///
+    /// 读者落后超过缓冲区容量时，`SkipForward` 读者返回一次 [`TapError::Lagged`]
+    /// 并从最旧的可用数据继续；`Drop` 读者被断开，之后的读取都返回 [`TapError::Detached`]。
+    pub fn read(&mut self, max: usize) -> Result<Vec<T>, TapError> {
+        let mut state = self.shared.state.lock().unwrap();
+        let oldest = state.oldest();
+        let write_pos = state.write_pos;
+        let reader = state.readers.get_mut(&self.id).expect("tap reader state missing");
+
+        if reader.detached {
+            return Err(TapError::Detached { skipped: reader.skipped });
+        }
+        if reader.cursor < oldest {
+            let lag = oldest - reader.cursor;
+            reader.skipped += lag;
+            match reader.policy {
+                LagPolicy::SkipForward => {
+                    reader.cursor = oldest;
+                    return Err(TapError::Lagged { skipped: lag });
+                }
+                LagPolicy::Drop => {
+                    reader.detached = true;
+                    return Err(TapError::Detached { skipped: reader.skipped });
+                }
+            }
+        }
+
+        let start = (reader.cursor - oldest) as usize;
+        let count = ((write_pos - reader.cursor) as usize).min(max);
+        reader.cursor += count as u64;
+        Ok(state.data.range(start..start + count).cloned().collect())
+    }
+
+    /// 异步读取恰好 `size` 个采样，数据不足时等待写入
+    ///
+    /// `size` 超过缓冲区容量时返回 [`TapError::TooLarge`]。
+    pub async fn read_exact(&mut self, size: usize) -> Result<Vec<T>, TapError> {
+        if size > self.shared.capacity {
+            return Err(TapError::TooLarge { requested: size, capacity: self.shared.capacity });
+        }
+        loop {
+            let shared = Arc::clone(&self.shared);
+            let notified = shared.data_available.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            // 数据足够、落后超过容量或已断开时立即读取（后两种情况返回错误）
+            if self.available() >= size || self.is_detached() {
+                return self.read(size);
+            }
+            notified.await;
+        }
+    }
+
+    /// 异步读取，等待至少 `size` 个采样或超时，返回当前可读的全部数据
+    pub async fn read_at_least(&mut self, size: usize, timeout: Duration) -> Result<Vec<T>, TapError> {
+        let _ = tokio::time::timeout(timeout, async {
+            loop {
+                let shared = Arc::clone(&self.shared);
+                let notified = shared.data_available.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if self.available() >= size || self.is_detached() {
+                    return;
+                }
+                notified.await;
+            }
+        })
+        .await;
+        self.read(usize::MAX)
+    }
+
+    /// 当前可以读取的采样数（不超过缓冲区容量）
+    pub fn available(&self) -> usize {
+        self.lag().min(self.shared.capacity as u64) as usize
+    }
+
+    /// 读者落后写入位置的采样数
+    pub fn lag(&self) -> u64 {
+        let state = self.shared.state.lock().unwrap();
+        state.write_pos - state.readers[&self.id].cursor
+    }
+
+    /// 累计因落后跳过的采样数
+    pub fn skipped(&self) -> u64 {
+        self.shared.state.lock().unwrap().readers[&self.id].skipped
+    }
+
+    /// 是否已被断开
+    pub fn is_detached(&self) -> bool {
+        self.shared.state.lock().unwrap().readers[&self.id].detached
+    }
+}
+
+impl<T> Drop for TapReader<T> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.readers.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_independent_readers() {
+        let tap = TapBuffer::new(16);
+        let mut vad = tap.attach(LagPolicy::SkipForward);
+        tap.write(&[1, 2, 3, 4]);
+
+        // 运行时接入的读者只看到之后写入的数据
+        let mut meter = tap.attach(LagPolicy::SkipForward);
+        tap.write(&[5, 6]);
+
+        assert_eq!(vad.read(3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(meter.read(10).unwrap(), vec![5, 6]);
+        assert_eq!(vad.read(10).unwrap(), vec![4, 5, 6]);
+        assert_eq!(tap.reader_count(), 2);
+
+        drop(meter);
+        assert_eq!(tap.reader_count(), 1);
+    }
+
+    #[test]
+    fn test_slow_readers_skip_or_drop() {
+        let tap = TapBuffer::new(8);
+        let mut skipper = tap.attach(LagPolicy::SkipForward);
+        let mut dropped = tap.attach(LagPolicy::Drop);
+        let mut fast = tap.attach(LagPolicy::Drop);
+
+        for chunk in (0..20).collect::<Vec<i32>>().chunks(4) {
+            tap.write(chunk);
+            assert_eq!(fast.read(4).unwrap(), chunk.to_vec());
+        }
+
+        // 20个采样写入容量为8的缓冲区，慢读者落后12个
+        assert_eq!(skipper.lag(), 20);
+        assert_eq!(skipper.read(4), Err(TapError::Lagged { skipped: 12 }));
+        assert_eq!(skipper.read(4).unwrap(), vec![12, 13, 14, 15]);
+        assert_eq!(skipper.skipped(), 12);
+
+        assert_eq!(dropped.read(4), Err(TapError::Detached { skipped: 12 }));
+        assert_eq!(dropped.read(4), Err(TapError::Detached { skipped: 12 }));
+        assert_eq!(tap.reader_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_async_tap_read() {
+        let tap = Arc::new(TapBuffer::new(64));
+        let mut reader = tap.attach(LagPolicy::SkipForward);
+
+        let writer = {
+            let tap = Arc::clone(&tap);
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                tap.write(&[7i16; 10]);
+            })
+        };
+
+        assert_eq!(reader.read_exact(10).await.unwrap(), vec![7; 10]);
+        writer.await.unwrap();
+        assert!(reader.read_at_least(1, Duration::from_millis(5)).await.unwrap().is_empty());
+
+        // 超过容量的请求永远等不满，立即返回错误
+        assert_eq!(reader.read_exact(65).await, Err(TapError::TooLarge { requested: 65, capacity: 64 }));
+    }
+}
@@ -0,0 +1,207 @@
+//! 自动增益控制模块
+//! 将语音电平拉到目标RMS，低于噪声门限时保持增益，避免把静音和底噪放大
+
+use serde::{Deserialize, Serialize};
+use crate::dsp::filters::db_to_linear;
+use crate::dsp::processor::AudioProcessor;
+
+const DETECTOR_MS: f32 = 10.0;      // 电平检测器的时间常数
+const PEAK_DECAY_DB_PER_S: f32 = 6.0; // 语音峰值电平的衰减速度
+const PAUSE_DROP_DB: f32 = 10.0;    // 电平低于近期峰值超过该值时视为停顿，不提高增益
+const UPDATE_INTERVAL: usize = 16;  // 每隔多少个采样更新一次增益
+const POWER_FLOOR: f32 = 1e-12;
+
+/// AGC参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgcConfig {
+    pub target_rms_db: f32,   // 目标RMS电平（dBFS）
+    pub attack_ms: f32,       // 降低增益的时间常数
+    pub release_ms: f32,      // 提高增益的时间常数
+    pub max_gain_db: f32,     // 最大增益
+    pub min_gain_db: f32,     // 最小增益（衰减上限）
+    pub noise_gate_db: f32,   // 低于该电平视为静音，保持当前增益
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        AgcConfig {
+            target_rms_db: -20.0,
+            attack_ms: 20.0,
+            release_ms: 400.0,
+            max_gain_db: 30.0,
+            min_gain_db: -20.0,
+            noise_gate_db: -55.0,
+        }
+    }
+}
+
+/// 自动增益控制器
+pub struct Agc {
+    config: AgcConfig,
+    detector_coeff: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    peak_decay_db: f32,  // 每次更新峰值电平衰减的dB数
+    power: f32,          // 平滑后的信号功率
+    peak_db: f32,        // 近期语音峰值电平
+    gain_db: f32,        // 当前增益
+    update_phase: usize, // 距上次更新增益的采样数，跨缓冲区连续计数
+}
+
+impl Agc {
+    /// 使用默认参数创建
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_config(sample_rate, AgcConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, config: AgcConfig) -> Self {
+        let coeff = |ms: f32| 1.0 - (-1000.0 / (ms.max(0.1) * sample_rate as f32)).exp();
+        Agc {
+            detector_coeff: coeff(DETECTOR_MS),
+            attack_coeff: coeff(config.attack_ms),
+            release_coeff: coeff(config.release_ms),
+            peak_decay_db: PEAK_DECAY_DB_PER_S * UPDATE_INTERVAL as f32 / sample_rate as f32,
+            config,
+            power: 0.0,
+            peak_db: f32::NEG_INFINITY,
+            gain_db: 0.0,
+            update_phase: 0,
+        }
+    }
+
+    /// 当前增益（dB）
+    pub fn current_gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// 当前检测到的输入电平（dBFS）
+    pub fn input_level_db(&self) -> f32 {
+        10.0 * self.power.max(POWER_FLOOR).log10()
+    }
+
+    /// AGC参数
+    pub fn config(&self) -> &AgcConfig {
+        &self.config
+    }
+}
+
+impl AudioProcessor for Agc {
+    fn name(&self) -> &str {
+        "agc"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let mut gain = db_to_linear(self.gain_db);
+        for sample in samples.iter_mut() {
+            let input = *sample;
+            self.power += (input * input - self.power) * self.detector_coeff;
+
+            // 每隔 UPDATE_INTERVAL 个采样更新一次增益，避免逐点计算对数；
+            // 计数跨缓冲区连续，更新频率（以及下面按步长换算的系数）与缓冲区长度无关
+            let update = self.update_phase == 0;
+            self.update_phase = (self.update_phase + 1) % UPDATE_INTERVAL;
+            if update {
+                let level_db = self.input_level_db();
+                self.peak_db = level_db.max(self.peak_db - self.peak_decay_db);
+
+                if level_db > self.config.noise_gate_db {
+                    let desired = (self.config.target_rms_db - level_db)
+                        .clamp(self.config.min_gain_db, self.config.max_gain_db);
+                    // 降低增益随时生效；提高增益只在语音持续时进行，
+                    // 否则语音结束后电平衰减的过程会把增益推高（“抽吸”）
+                    let paused = level_db < self.peak_db - PAUSE_DROP_DB;
+                    let coeff = if desired < self.gain_db {
+                        Some(self.attack_coeff)
+                    } else if !paused {
+                        Some(self.release_coeff)
+                    } else {
+                        None
+                    };
+                    if let Some(coeff) = coeff {
+                        // 系数按更新步长换算
+                        let step = 1.0 - (1.0 - coeff).powi(UPDATE_INTERVAL as i32);
+                        self.gain_db += (desired - self.gain_db) * step;
+                        gain = db_to_linear(self.gain_db);
+                    }
+                }
+            }
+
+            *sample = input * gain;
+        }
+    }
+
+    fn gain_db(&self) -> Option<f32> {
+        Some(self.gain_db)
+    }
+
+    fn reset(&mut self) {
+        self.power = 0.0;
+        self.peak_db = f32::NEG_INFINITY;
+        self.gain_db = 0.0;
+        self.update_phase = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(amplitude_db: f32, count: usize) -> Vec<f32> {
+        // 正弦波RMS比峰值低3dB，这里按RMS给定电平
+        let amplitude = db_to_linear(amplitude_db) * 2f32.sqrt();
+        (0..count)
+            .map(|i| (2.0 * std::f32::consts::PI * 300.0 * i as f32 / 16000.0).sin() * amplitude)
+            .collect()
+    }
+
+    fn rms_db(samples: &[f32]) -> f32 {
+        let power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+        10.0 * power.log10()
+    }
+
+    #[test]
+    fn test_agc_normalizes_quiet_and_loud_speakers() {
+        // 轻声（-45dBFS）和大声（-8dBFS）都被拉到 -20dBFS 附近
+        for level in [-45.0, -8.0] {
+            let mut agc = Agc::new(16000);
+            let mut signal = tone(level, 16000 * 4);
+            agc.process(&mut signal);
+            let out = rms_db(&signal[16000 * 3..]);
+            assert!((out + 20.0).abs() < 1.0, "input {} dB -> output {} dB", level, out);
+        }
+    }
+
+    #[test]
+    fn test_agc_noise_gate_holds_gain() {
+        let mut agc = Agc::new(16000);
+        let mut speech = tone(-30.0, 16000 * 3);
+        agc.process(&mut speech);
+        let speech_gain = agc.current_gain_db();
+        assert!((speech_gain - 10.0).abs() < 1.0);
+
+        // 静音段（-70dBFS底噪）不会把增益继续推高
+        let mut silence = tone(-70.0, 16000 * 3);
+        agc.process(&mut silence);
+        assert!((agc.current_gain_db() - speech_gain).abs() < 0.5);
+        assert_eq!(agc.gain_db(), Some(agc.current_gain_db()));
+    }
+
+    #[test]
+    fn test_agc_gain_independent_of_buffer_size() {
+        // 同一段信号整块处理与按10个采样（不是16的倍数）分块处理，增益轨迹一致
+        let signal = tone(-40.0, 16000);
+        let mut whole = Agc::new(16000);
+        let mut expected = signal.clone();
+        whole.process(&mut expected);
+
+        let mut chunked = Agc::new(16000);
+        let mut actual = signal;
+        for chunk in actual.chunks_mut(10) {
+            chunked.process(chunk);
+        }
+        assert!((chunked.current_gain_db() - whole.current_gain_db()).abs() < 1e-4);
+        assert!(actual.iter().zip(&expected).all(|(a, e)| (a - e).abs() < 1e-5));
+    }
+}
@@ -0,0 +1,255 @@
+//! 声道映射模块
+//! 将多声道设备输入混合为单声道，并将单声道输出扩展到设备的任意声道布局
+
+const SNR_SWITCH_RATIO: f32 = 2.0;    // 新声道信噪比需高出当前声道约3dB才切换
+const SIGNAL_SMOOTHING: f32 = 0.3;    // 信号能量平滑系数（快速）
+const NOISE_RISE_RATE: f32 = 1.01;    // 噪声底每个缓冲区允许的上升比例（慢速）
+const ENERGY_FLOOR: f32 = 1e-10;
+
+/// 下混模式（多声道 -> 单声道）
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownmixMode {
+    Average,         // 所有声道取平均
+    Select(usize),   // 只使用指定声道（从0开始）
+    BestSnr,         // 自动选择信噪比最高的声道
+}
+
+/// 上混模式（单声道 -> 多声道）
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpmixMode {
+    AllChannels,          // 复制到所有声道
+    Channels(Vec<usize>), // 只输出到指定声道，其余声道静音
+}
+
+/// 单个设备的声道映射配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMapping {
+    pub downmix: DownmixMode,
+    pub upmix: UpmixMode,
+}
+
+impl Default for ChannelMapping {
+    fn default() -> Self {
+        ChannelMapping {
+            downmix: DownmixMode::Average,
+            upmix: UpmixMode::AllChannels,
+        }
+    }
+}
+
+/// 每个声道的能量统计，用于信噪比选择
+#[derive(Debug, Clone, Copy)]
+struct ChannelStats {
+    signal: f32,
+    noise: f32,
+}
+
+/// 下混器，将交错排列的N声道数据转换为单声道
+pub struct Downmixer {
+    mode: DownmixMode,
+    channels: usize,
+    stats: Vec<ChannelStats>,
+    active_channel: usize,
+}
+
+impl Downmixer {
+    /// 创建新的下混器
+    pub fn new(mode: DownmixMode, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let active_channel = match mode {
+            DownmixMode::Select(channel) => channel.min(channels - 1),
+            _ => 0,
+        };
+
+        Downmixer {
+            mode,
+            channels,
+            stats: vec![ChannelStats { signal: 0.0, noise: f32::MAX }; channels],
+            active_channel,
+        }
+    }
+
+    /// 当前使用的声道（仅对 Select 和 BestSnr 模式有意义）
+    pub fn active_channel(&self) -> usize {
+        self.active_channel
+    }
+
+    /// 下混一段交错排列的数据
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::with_capacity(input.len() / self.channels);
+        self.process_into(input, &mut output);
+        output
+    }
+
+    /// 下混并将结果追加到 `output`
+    pub fn process_into(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.channels == 1 {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let frames = input.chunks_exact(self.channels);
+        match self.mode {
+            DownmixMode::Average => {
+                let scale = 1.0 / self.channels as f32;
+                output.extend(frames.map(|frame| frame.iter().sum::<f32>() * scale));
+            }
+            DownmixMode::Select(_) => {
+                let channel = self.active_channel;
+                output.extend(frames.map(|frame| frame[channel]));
+            }
+            DownmixMode::BestSnr => {
+                let previous = self.active_channel;
+                self.update_snr(input);
+                let current = self.active_channel;
+
+                if previous == current {
+                    output.extend(frames.map(|frame| frame[current]));
+                } else {
+                    // 切换声道时在整个缓冲区内交叉淡化，避免爆音
+                    let count = (input.len() / self.channels).max(1) as f32;
+                    output.extend(frames.enumerate().map(|(i, frame)| {
+                        let t = i as f32 / count;
+                        frame[previous] * (1.0 - t) + frame[current] * t
+                    }));
+                }
+            }
+        }
+    }
+
+    /// 更新每个声道的信号与噪声估计，并选择信噪比最高的声道
+    fn update_snr(&mut self, input: &[f32]) {
+        let frame_count = (input.len() / self.channels).max(1) as f32;
+        for (channel, stats) in self.stats.iter_mut().enumerate() {
+            let power = input
+                .iter()
+                .skip(channel)
+                .step_by(self.channels)
+                .map(|s| s * s)
+                .sum::<f32>()
+                / frame_count
+                + ENERGY_FLOOR;
+
+            stats.signal += (power - stats.signal) * SIGNAL_SMOOTHING;
+            // 最小值跟踪：能量低于噪声底时立即下降，否则缓慢上升
+            stats.noise = if power < stats.noise {
+                power
+            } else {
+                stats.noise * NOISE_RISE_RATE
+            };
+        }
+
+        let snr = |stats: &ChannelStats| stats.signal / stats.noise.max(ENERGY_FLOOR);
+        let current_snr = snr(&self.stats[self.active_channel]);
+        let (best, best_snr) = self
+            .stats
+            .iter()
+            .enumerate()
+            .map(|(channel, stats)| (channel, snr(stats)))
+            .fold((self.active_channel, current_snr), |best, candidate| {
+                if candidate.1 > best.1 { candidate } else { best }
+            });
+
+        if best != self.active_channel && best_snr > current_snr * SNR_SWITCH_RATIO {
+            self.active_channel = best;
+        }
+    }
+}
+
+/// 上混器，将单声道数据写入N声道交错缓冲区
+pub struct Upmixer {
+    channels: usize,
+    gains: Vec<f32>,  // 每个声道的增益（0.0 表示静音）
+}
+
+impl Upmixer {
+    /// 创建新的上混器
+    pub fn new(mode: &UpmixMode, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let gains = match mode {
+            UpmixMode::AllChannels => vec![1.0; channels],
+            UpmixMode::Channels(selected) => {
+                let mut gains = vec![0.0; channels];
+                for &channel in selected.iter().filter(|&&c| c < channels) {
+                    gains[channel] = 1.0;
+                }
+                gains
+            }
+        };
+
+        Upmixer { channels, gains }
+    }
+
+    /// 设备声道数
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// 将一个单声道采样写入一个设备帧
+    pub fn write_frame(&self, sample: f32, frame: &mut [f32]) {
+        for (out, gain) in frame.iter_mut().zip(self.gains.iter()) {
+            *out = sample * gain;
+        }
+    }
+
+    /// 上混一段单声道数据
+    pub fn process(&self, input: &[f32]) -> Vec<f32> {
+        let mut output = vec![0.0; input.len() * self.channels];
+        for (frame, &sample) in output.chunks_exact_mut(self.channels).zip(input.iter()) {
+            self.write_frame(sample, frame);
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downmix_average_and_select() {
+        let stereo = vec![0.5, -0.5, 1.0, 0.0, 0.25, 0.75];
+
+        let mut average = Downmixer::new(DownmixMode::Average, 2);
+        assert_eq!(average.process(&stereo), vec![0.0, 0.5, 0.5]);
+
+        let mut right = Downmixer::new(DownmixMode::Select(1), 2);
+        assert_eq!(right.process(&stereo), vec![-0.5, 0.0, 0.75]);
+
+        // 超出范围的声道索引回退到最后一个声道
+        let clamped = Downmixer::new(DownmixMode::Select(7), 2);
+        assert_eq!(clamped.active_channel(), 1);
+    }
+
+    #[test]
+    fn test_downmix_best_snr() {
+        let mut downmixer = Downmixer::new(DownmixMode::BestSnr, 2);
+
+        // 左声道只有稳定噪声，右声道是安静底噪之上的间歇性语音
+        let mut phase = 0.0f32;
+        for block in 0..40 {
+            let speaking = block % 4 != 0;
+            let input: Vec<f32> = (0..160)
+                .flat_map(|i| {
+                    phase += 0.1;
+                    let noise = if i % 2 == 0 { 0.1 } else { -0.1 };
+                    let voice = if speaking { phase.sin() * 0.5 } else { 0.001 };
+                    [noise, voice]
+                })
+                .collect();
+            downmixer.process(&input);
+        }
+
+        assert_eq!(downmixer.active_channel(), 1);
+    }
+
+    #[test]
+    fn test_upmix_layouts() {
+        let all = Upmixer::new(&UpmixMode::AllChannels, 2);
+        assert_eq!(all.process(&[0.5, -0.25]), vec![0.5, 0.5, -0.25, -0.25]);
+
+        // 5.1 布局只输出到前置左右声道
+        let front = Upmixer::new(&UpmixMode::Channels(vec![0, 1]), 6);
+        assert_eq!(front.process(&[1.0]), vec![1.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,296 @@
+//! 舒适噪声模块
+//! 测量用户环境的背景噪声谱，在虚拟麦克风输出静音时填充同频谱形状的低电平噪声
+
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::rng::XorShift32;
+use crate::core::sample::Sample;
+use crate::dsp::fft::{hann_window, Complex, Fft};
+use crate::dsp::filters::{db_to_linear, linear_to_db};
+
+const FRAME_MS: f32 = 32.0;        // 分析/合成帧长度（向上取到2的幂）
+const POWER_SMOOTHING: f32 = 0.9;  // 背景跟踪用的功率谱平滑系数
+const MIN_BIAS: f32 = 1.5;         // 补偿最小值跟踪对平稳噪声功率的低估
+const INIT_FRAMES: usize = 8;      // 启动阶段直接用平均功率初始化背景谱
+const RISE_DB_PER_S: f32 = 3.0;    // 背景估计允许上升的速度
+
+/// 舒适噪声参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComfortNoiseConfig {
+    pub level_offset_db: f32,  // 相对测得背景的电平偏移
+    pub min_level_db: f32,     // 舒适噪声电平下限（dBFS），背景未测得时也使用该电平
+    pub max_level_db: f32,     // 舒适噪声电平上限（dBFS），避免嘈杂环境下填充过响的噪声
+    pub silence_db: f32,       // 输出帧 RMS 低于该电平视为静音
+    pub fade_in_ms: f32,       // 静音开始后舒适噪声的淡入时间
+}
+
+impl Default for ComfortNoiseConfig {
+    fn default() -> Self {
+        ComfortNoiseConfig {
+            level_offset_db: -6.0,
+            min_level_db: -80.0,
+            max_level_db: -50.0,
+            silence_db: -70.0,
+            fade_in_ms: 50.0,
+        }
+    }
+}
+
+/// 舒适噪声发生器
+///
+/// 背景谱用最小值跟踪估计（语音上的功率峰值不计入背景），合成时按背景谱幅度配随机相位，
+/// 经逆变换后用平方根Hann窗50%重叠相加，输出与背景同谱形状、同方差的平稳噪声。
+pub struct ComfortNoise {
+    config: ComfortNoiseConfig,
+    fft: Fft,
+    hop: usize,
+    analysis_window: Vec<f32>,   // Hann窗
+    synthesis_window: Vec<f32>,  // 平方根Hann窗，50%重叠时平方和为1
+    window_energy: f32,
+    rise: f32,
+    input: Vec<f32>,             // 待分析的测量音频
+    smoothed_power: Vec<f32>,
+    noise_power: Vec<f32>,       // 背景功率谱（Hann窗加权后的 |X|²）
+    frames: usize,
+    overlap: Vec<f32>,
+    generated: Vec<f32>,         // 已合成、待输出的噪声
+    fade_step: f32,
+    gain: f32,                   // 当前淡入增益，停止时归零
+    rng: XorShift32,             // 随机相位
+    spectrum: Vec<Complex>,
+    frame: Vec<f32>,
+}
+
+impl ComfortNoise {
+    /// 使用默认参数创建
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_config(sample_rate, ComfortNoiseConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, config: ComfortNoiseConfig) -> Self {
+        let size = ((sample_rate as f32 * FRAME_MS / 1000.0) as usize).next_power_of_two().max(64);
+        let hop = size / 2;
+        let analysis_window = hann_window(size);
+        let window_energy: f32 = analysis_window.iter().map(|w| w * w).sum();
+        let hop_seconds = hop as f32 / sample_rate as f32;
+        let fade_samples = (sample_rate as f32 * config.fade_in_ms.max(0.0) / 1000.0).max(1.0);
+
+        // 背景未测得时按下限电平的白噪声
+        let floor = db_to_linear(config.min_level_db);
+        let white = floor * floor * window_energy;
+
+        ComfortNoise {
+            fft: Fft::new(size),
+            hop,
+            synthesis_window: analysis_window.iter().map(|w| w.sqrt()).collect(),
+            analysis_window,
+            window_energy,
+            rise: 10f32.powf(RISE_DB_PER_S * hop_seconds / 10.0),
+            input: Vec::with_capacity(size),
+            smoothed_power: vec![0.0; size / 2 + 1],
+            noise_power: vec![white; size / 2 + 1],
+            frames: 0,
+            overlap: vec![0.0; size],
+            generated: Vec::new(),
+            fade_step: 1.0 / fade_samples,
+            gain: 0.0,
+            rng: XorShift32::new(0x2545_F491),
+            spectrum: Vec::new(),
+            frame: Vec::new(),
+            config,
+        }
+    }
+
+    /// 舒适噪声参数
+    pub fn config(&self) -> &ComfortNoiseConfig {
+        &self.config
+    }
+
+    /// 测得的背景电平（dBFS RMS）
+    pub fn background_db(&self) -> f32 {
+        let size = self.fft.size();
+        let last = self.noise_power.len() - 1;
+        let total: f32 = self.noise_power.iter()
+            .enumerate()
+            .map(|(k, &p)| if k == 0 || k == last { p } else { 2.0 * p })
+            .sum();
+        10.0 * (total / (size as f32 * self.window_energy)).max(1e-20).log10()
+    }
+
+    /// 舒适噪声的输出电平（dBFS RMS）
+    pub fn level_db(&self) -> f32 {
+        (self.background_db() + self.config.level_offset_db)
+            .clamp(self.config.min_level_db, self.config.max_level_db.max(self.config.min_level_db))
+    }
+
+    /// 测量背景：送入用户麦克风的原始采集音频
+    pub fn measure(&mut self, samples: &[f32]) {
+        let size = self.fft.size();
+        for &sample in samples {
+            self.input.push(sample);
+            if self.input.len() < size {
+                continue;
+            }
+
+            // 数字静音（麦克风被静音或断开）不是环境背景，保留之前的估计
+            let power = self.input.iter().map(|x| x * x).sum::<f32>() / size as f32;
+            if linear_to_db(power.sqrt()) < self.config.min_level_db {
+                self.input.drain(..self.hop);
+                continue;
+            }
+
+            self.frame.clear();
+            self.frame.extend(self.input.iter().zip(self.analysis_window.iter()).map(|(x, w)| x * w));
+            self.fft.forward_real(&self.frame, &mut self.spectrum);
+            self.frames += 1;
+
+            for (k, bin) in self.spectrum.iter().enumerate() {
+                let power = bin.norm_sqr();
+                if self.frames <= INIT_FRAMES {
+                    // 启动阶段取平均功率
+                    let n = self.frames as f32;
+                    self.smoothed_power[k] = if self.frames == 1 { power } else { (self.smoothed_power[k] * (n - 1.0) + power) / n };
+                    self.noise_power[k] = self.smoothed_power[k];
+                } else {
+                    self.smoothed_power[k] = POWER_SMOOTHING * self.smoothed_power[k] + (1.0 - POWER_SMOOTHING) * power;
+                    self.noise_power[k] = (self.noise_power[k] * self.rise).min(self.smoothed_power[k] * MIN_BIAS);
+                }
+            }
+            self.input.drain(..self.hop);
+        }
+    }
+
+    /// 测量流水线采样格式的背景音频
+    pub fn measure_audio(&mut self, samples: &[AudioSample]) {
+        let samples: Vec<f32> = samples.iter().map(|s| s.to_f32()).collect();
+        self.measure(&samples);
+    }
+
+    /// 输出帧为静音时叠加舒适噪声（带淡入），否则停止；返回是否填充
+    pub fn fill(&mut self, output: &mut [f32]) -> bool {
+        if output.is_empty() {
+            return false;
+        }
+        let rms = (output.iter().map(|x| x * x).sum::<f32>() / output.len() as f32).sqrt();
+        if linear_to_db(rms) >= self.config.silence_db {
+            self.stop();
+            return false;
+        }
+
+        let mut noise = vec![0.0; output.len()];
+        self.generate(&mut noise);
+        for (out, value) in output.iter_mut().zip(noise) {
+            self.gain = (self.gain + self.fade_step).min(1.0);
+            *out += value * self.gain;
+        }
+        true
+    }
+
+    /// 立即停止舒适噪声（例如译文语音到达），下次填充时重新淡入
+    pub fn stop(&mut self) {
+        self.gain = 0.0;
+    }
+
+    /// 生成舒适噪声
+    pub fn generate(&mut self, output: &mut [f32]) {
+        // 背景谱幅度换算到输出电平：随机相位帧逆变换后的方差为 Σ|M|² / N²
+        let size = self.fft.size();
+        let scale = db_to_linear(self.level_db() - self.background_db());
+        let magnitude_scale = scale * (size as f32 / self.window_energy).sqrt();
+
+        let mut written = 0;
+        while written < output.len() {
+            if self.generated.is_empty() {
+                self.synthesize_hop(magnitude_scale);
+            }
+            let count = self.generated.len().min(output.len() - written);
+            output[written..written + count].copy_from_slice(&self.generated[..count]);
+            self.generated.drain(..count);
+            written += count;
+        }
+    }
+
+    /// 合成一帧随机相位噪声并重叠相加，产出一个跳步
+    fn synthesize_hop(&mut self, magnitude_scale: f32) {
+        let size = self.fft.size();
+        let last = self.noise_power.len() - 1;
+        let mut spectrum = std::mem::take(&mut self.spectrum);
+        spectrum.clear();
+        for k in 0..=last {
+            let magnitude = self.noise_power[k].sqrt() * magnitude_scale;
+            spectrum.push(if k == 0 || k == last {
+                // 直流和奈奎斯特频点必须为实数
+                let sign = if self.rng.next_uniform() < 0.5 { -1.0 } else { 1.0 };
+                Complex::new(magnitude * sign, 0.0)
+            } else {
+                let phase = 2.0 * std::f32::consts::PI * self.rng.next_uniform();
+                Complex::new(magnitude * phase.cos(), magnitude * phase.sin())
+            });
+        }
+        self.fft.inverse_real(&spectrum, &mut self.frame);
+        self.spectrum = spectrum;
+
+        for i in 0..size {
+            self.overlap[i] += self.frame[i] * self.synthesis_window[i];
+        }
+        self.generated.extend_from_slice(&self.overlap[..self.hop]);
+        self.overlap.copy_within(self.hop.., 0);
+        self.overlap[size - self.hop..].iter_mut().for_each(|x| *x = 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rng::tests::white_noise;
+    use crate::dsp::level_meter::{LevelMeter, LevelMeterConfig};
+
+    const RATE: u32 = 16000;
+
+    #[test]
+    fn test_comfort_noise_matches_background() {
+        // 低频为主的背景噪声（白噪声经一阶低通），约 -45 dBFS
+        let mut lowpassed = 0.0f32;
+        let background: Vec<f32> = white_noise(RATE as usize * 2, 0.5, 12345)
+            .into_iter()
+            .map(|white| {
+                lowpassed = 0.9 * lowpassed + 0.1 * white;
+                lowpassed * 0.05
+            })
+            .collect();
+        let rms_db = |s: &[f32]| linear_to_db((s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt());
+
+        let config = ComfortNoiseConfig { level_offset_db: 0.0, max_level_db: -30.0, ..Default::default() };
+        let mut comfort = ComfortNoise::with_config(RATE, config);
+        assert!((comfort.level_db() + 80.0).abs() < 0.01);
+        comfort.measure(&background);
+        assert!((comfort.background_db() - rms_db(&background)).abs() < 3.0,
+                "{} vs {}", comfort.background_db(), rms_db(&background));
+
+        // 静音帧被填充为同电平、同谱形状的噪声
+        let mut output = vec![0.0f32; RATE as usize];
+        for chunk in output.chunks_mut(320) {
+            assert!(comfort.fill(chunk));
+        }
+        let steady = &output[1600..];
+        assert!((rms_db(steady) - comfort.level_db()).abs() < 1.5, "{} vs {}", rms_db(steady), comfort.level_db());
+
+        let spectrum = |s: &[f32]| {
+            let mut meter = LevelMeter::with_config(RATE, LevelMeterConfig { update_ms: 900, spectrum_bands: 4, ..Default::default() });
+            meter.push(s).pop().unwrap().bands
+        };
+        let expected = spectrum(&background[..14400]);
+        let generated = spectrum(&steady[..14400]);
+        for (a, b) in expected.iter().zip(generated.iter()) {
+            assert!((a.level_db - b.level_db).abs() < 4.0, "{:?} vs {:?}", a, b);
+        }
+        assert!(generated[0].level_db > generated[3].level_db + 5.0, "{:?}", generated);
+
+        // 非静音帧原样保留
+        let mut speech = vec![0.1f32; 320];
+        assert!(!comfort.fill(&mut speech));
+        assert!(speech.iter().all(|&x| x == 0.1));
+    }
+}
@@ -0,0 +1,183 @@
+//! 闪避混音模块
+//! 像同传译员一样把原声压低后垫在译文语音下面，译文开始和结束时平滑交叉淡入淡出
+
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::Sample;
+use crate::dsp::filters::{db_to_linear, linear_to_db};
+
+/// 闪避参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuckingConfig {
+    pub duck_db: f32,             // 译文播放期间原声的衰减量
+    pub duck_fade_ms: f32,        // 原声压低的淡出时间
+    pub restore_fade_ms: f32,     // 原声恢复的淡入时间
+    pub hold_ms: f32,             // 译文结束后保持压低的时间，避免句间来回起伏
+    pub translation_fade_ms: f32, // 译文语音首尾的淡入淡出时间，避免咔哒声
+}
+
+impl Default for DuckingConfig {
+    fn default() -> Self {
+        DuckingConfig {
+            duck_db: -18.0,
+            duck_fade_ms: 80.0,
+            restore_fade_ms: 400.0,
+            hold_ms: 300.0,
+            translation_fade_ms: 10.0,
+        }
+    }
+}
+
+/// 闪避混音器
+///
+/// 原声按采集节奏逐帧送入 [`DuckingMixer::mix`]，译文语音预先排队；
+/// 有译文时原声按 `duck_fade_ms` 线性淡到 `duck_db`，译文播完并经过 `hold_ms` 后按 `restore_fade_ms` 恢复。
+pub struct DuckingMixer {
+    config: DuckingConfig,
+    translation: VecDeque<f32>,
+    duck_gain: f32,        // 闪避后的原声增益（线性）
+    down_step: f32,        // 每个采样的增益下降量
+    up_step: f32,          // 每个采样的增益恢复量
+    hold_samples: usize,
+    fade_samples: usize,
+    gain: f32,             // 当前原声增益
+    hold_remaining: usize,
+    fade_in_pos: usize,    // 当前译文片段已淡入的采样数
+}
+
+impl DuckingMixer {
+    /// 使用默认参数创建
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_config(sample_rate, DuckingConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, config: DuckingConfig) -> Self {
+        let samples = |ms: f32| (sample_rate as f32 * ms.max(0.0) / 1000.0) as usize;
+        let duck_gain = db_to_linear(config.duck_db.min(0.0));
+        let depth = 1.0 - duck_gain;
+        DuckingMixer {
+            translation: VecDeque::new(),
+            duck_gain,
+            down_step: depth / samples(config.duck_fade_ms).max(1) as f32,
+            up_step: depth / samples(config.restore_fade_ms).max(1) as f32,
+            hold_samples: samples(config.hold_ms),
+            fade_samples: samples(config.translation_fade_ms).max(1),
+            gain: 1.0,
+            hold_remaining: 0,
+            fade_in_pos: 0,
+            config,
+        }
+    }
+
+    /// 闪避参数
+    pub fn config(&self) -> &DuckingConfig {
+        &self.config
+    }
+
+    /// 译文语音排队等待混音
+    pub fn push_translation(&mut self, samples: &[f32]) {
+        self.translation.extend(samples.iter().copied());
+    }
+
+    /// 流水线采样格式的译文语音排队
+    pub fn push_translation_audio(&mut self, samples: &[AudioSample]) {
+        self.translation.extend(samples.iter().map(|s| s.to_f32()));
+    }
+
+    /// 尚未混出的译文采样数
+    pub fn pending_translation(&self) -> usize {
+        self.translation.len()
+    }
+
+    /// 是否有译文正在播放
+    pub fn is_translating(&self) -> bool {
+        !self.translation.is_empty()
+    }
+
+    /// 当前原声增益（dB）
+    pub fn current_duck_db(&self) -> f32 {
+        linear_to_db(self.gain)
+    }
+
+    /// 把排队的译文混入一段原声（原地）
+    pub fn mix(&mut self, original: &mut [f32]) {
+        for sample in original.iter_mut() {
+            let translating = !self.translation.is_empty();
+            if translating {
+                self.hold_remaining = self.hold_samples;
+            } else {
+                self.hold_remaining = self.hold_remaining.saturating_sub(1);
+            }
+
+            let target = if translating || self.hold_remaining > 0 { self.duck_gain } else { 1.0 };
+            self.gain = if self.gain > target {
+                (self.gain - self.down_step).max(target)
+            } else {
+                (self.gain + self.up_step).min(target)
+            };
+
+            let translation = match self.translation.pop_front() {
+                Some(value) => {
+                    // 片段开头淡入、队列末尾淡出
+                    self.fade_in_pos = (self.fade_in_pos + 1).min(self.fade_samples);
+                    let fade_out = (self.translation.len() + 1).min(self.fade_samples);
+                    value * self.fade_in_pos.min(fade_out) as f32 / self.fade_samples as f32
+                }
+                None => {
+                    self.fade_in_pos = 0;
+                    0.0
+                }
+            };
+
+            *sample = *sample * self.gain + translation;
+        }
+    }
+
+    /// 清空排队的译文并恢复原声
+    pub fn reset(&mut self) {
+        self.translation.clear();
+        self.gain = 1.0;
+        self.hold_remaining = 0;
+        self.fade_in_pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: usize = 16000;
+
+    #[test]
+    fn test_original_ducked_under_translation() {
+        let config = DuckingConfig { duck_db: -20.0, hold_ms: 100.0, ..Default::default() };
+        let mut mixer = DuckingMixer::with_config(RATE as u32, config);
+
+        // 0.5秒译文在第0.2秒开始排队
+        let mut output = vec![0.5f32; RATE * 2];
+        let (before, rest) = output.split_at_mut(RATE / 5);
+        mixer.mix(before);
+        mixer.push_translation(&vec![0.25f32; RATE / 2]);
+        assert!(mixer.is_translating());
+        for chunk in rest.chunks_mut(320) {
+            mixer.mix(chunk);
+        }
+        assert!(!mixer.is_translating());
+
+        // 译文之前原声不变；译文期间原声压低20dB（0.05）叠加译文
+        assert_eq!(output[RATE / 10], 0.5);
+        assert!((output[RATE / 2] - (0.05 + 0.25)).abs() < 1e-4);
+        // 译文结束后保持100ms，再经400ms恢复
+        let translation_end = RATE / 5 + RATE / 2;
+        assert!((output[translation_end + RATE / 20] - 0.05).abs() < 1e-4);
+        assert!((output[translation_end + RATE / 10 + RATE * 2 / 5 + 10] - 0.5).abs() < 1e-4);
+        assert!((mixer.current_duck_db()).abs() < 1e-3);
+
+        // 所有过渡都是平滑的：相邻采样的变化不超过一个淡入步长
+        let max_jump = output.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+        assert!(max_jump < 0.25 / 160.0 + 0.45 / 1280.0 + 1e-4, "jump {}", max_jump);
+    }
+}
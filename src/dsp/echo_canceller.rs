@@ -0,0 +1,359 @@
+//! 回声消除模块
+//! 以耳机播放的音频为参考信号，估计整体延迟后用NLMS自适应滤波器从麦克风信号中减去回声
+
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::Sample;
+use crate::core::tap_buffer::{TapError, TapReader};
+use crate::dsp::fft::{Complex, Fft};
+use crate::dsp::processor::AudioProcessor;
+
+const ESTIMATE_MS: f32 = 250.0;       // 延迟估计的分析块长度（向上取到2的幂）
+const MIN_REFERENCE_POWER: f32 = 1e-6; // 参考信号低于该功率时视为静音，不估计延迟也不自适应
+const PEAK_RATIO: f32 = 6.0;          // 互相关峰值需超过平均值的倍数才认为估计可靠
+const DOUBLE_TALK_RATIO: f32 = 0.5;   // 双讲检测：麦克风短时幅度超过参考信号的该比例时视为近端说话
+const DOUBLE_TALK_HOLD_MS: f32 = 30.0; // 检测到双讲后暂停自适应的时间
+const DETECTOR_MS: f32 = 10.0;        // 双讲检测的麦克风功率时间常数
+const ERLE_SMOOTHING_MS: f32 = 200.0;
+const REGULARIZATION: f32 = 1e-4;
+
+/// 回声消除参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EchoCancellerConfig {
+    pub filter_ms: f32,     // 自适应滤波器长度（对齐延迟之后的房间响应）
+    pub max_delay_ms: f32,  // 可估计的最大回声延迟（输出队列 + 设备 + 声学路径）
+    pub step_size: f32,     // NLMS步长（0~1）
+}
+
+impl Default for EchoCancellerConfig {
+    fn default() -> Self {
+        EchoCancellerConfig {
+            filter_ms: 32.0,
+            max_delay_ms: 500.0,
+            step_size: 0.3,
+        }
+    }
+}
+
+/// 定长历史缓冲区，数据写两份，任意窗口都能以连续切片读取
+struct History {
+    data: Vec<f32>,
+    capacity: usize,
+    pos: usize,
+}
+
+impl History {
+    fn new(capacity: usize) -> Self {
+        History { data: vec![0.0; capacity * 2], capacity, pos: 0 }
+    }
+
+    fn push(&mut self, value: f32) {
+        self.data[self.pos] = value;
+        self.data[self.pos + self.capacity] = value;
+        self.pos = (self.pos + 1) % self.capacity;
+    }
+
+    /// 最新采样之前 `offset` 个采样结束的 `len` 个采样（从旧到新）
+    fn window(&self, len: usize, offset: usize) -> &[f32] {
+        let end = self.pos + self.capacity - offset;
+        &self.data[end - len..end]
+    }
+
+    fn clear(&mut self) {
+        self.data.fill(0.0);
+        self.pos = 0;
+    }
+}
+
+/// 声学回声消除器
+///
+/// 参考信号通过耳机输出流的分接读者获取，按麦克风采样的节奏逐点消费
+/// （没有播放时补零），因此输出队列造成的延迟表现为固定的整体延迟，
+/// 由PHAT加权互相关估计；自适应滤波器只需覆盖对齐后的房间响应。
+pub struct EchoCanceller {
+    config: EchoCancellerConfig,
+    reference: TapReader<AudioSample>,
+    pending: VecDeque<f32>,     // 已到达但尚未与麦克风对齐消费的参考采样
+    ref_history: History,
+    mic_history: History,
+    weights: Vec<f32>,          // 滤波器系数，与参考窗口同序（从旧到新）
+    filter_len: usize,
+    max_delay: usize,
+    delay: Option<usize>,       // 当前使用的整体延迟（采样数）
+    candidate: Option<usize>,   // 待确认的新延迟估计
+    fft: Fft,
+    block: usize,
+    since_estimate: usize,
+    double_talk_hold: usize,
+    double_talk_samples: usize,
+    detector_coeff: f32,
+    near_power: f32,            // 麦克风短时功率（双讲检测）
+    erle_coeff: f32,
+    mic_power: f32,
+    residual_power: f32,
+}
+
+impl EchoCanceller {
+    /// 使用默认参数创建，`reference` 为耳机输出流的分接读者
+    pub fn new(sample_rate: u32, reference: TapReader<AudioSample>) -> Self {
+        Self::with_config(sample_rate, reference, EchoCancellerConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, reference: TapReader<AudioSample>, config: EchoCancellerConfig) -> Self {
+        let samples = |ms: f32| (sample_rate as f32 * ms.max(0.0) / 1000.0) as usize;
+        let filter_len = samples(config.filter_ms).max(1);
+        let max_delay = samples(config.max_delay_ms);
+        let block = samples(ESTIMATE_MS).next_power_of_two();
+        let fft_size = (block * 2 + max_delay).next_power_of_two();
+
+        EchoCanceller {
+            reference,
+            pending: VecDeque::new(),
+            ref_history: History::new(block + max_delay + filter_len),
+            mic_history: History::new(block),
+            weights: vec![0.0; filter_len],
+            filter_len,
+            max_delay,
+            delay: None,
+            candidate: None,
+            fft: Fft::new(fft_size),
+            block,
+            since_estimate: 0,
+            double_talk_hold: samples(DOUBLE_TALK_HOLD_MS),
+            double_talk_samples: 0,
+            detector_coeff: 1.0 - (-1000.0 / (DETECTOR_MS * sample_rate as f32)).exp(),
+            near_power: 0.0,
+            erle_coeff: 1.0 - (-1000.0 / (ERLE_SMOOTHING_MS * sample_rate as f32)).exp(),
+            mic_power: 0.0,
+            residual_power: 0.0,
+            config,
+        }
+    }
+
+    /// 当前估计的回声延迟（采样数），尚未估计出时返回 None
+    pub fn estimated_delay(&self) -> Option<usize> {
+        self.delay
+    }
+
+    /// 回声回波损耗增强（ERLE，dB）：麦克风功率与消除后残差功率之比
+    pub fn erle_db(&self) -> f32 {
+        10.0 * (self.mic_power.max(1e-12) / self.residual_power.max(1e-12)).log10()
+    }
+
+    /// 回声消除参数
+    pub fn config(&self) -> &EchoCancellerConfig {
+        &self.config
+    }
+
+    /// 取出分接读者上已到达的参考音频
+    fn pull_reference(&mut self) {
+        loop {
+            match self.reference.read(usize::MAX) {
+                Ok(samples) => {
+                    self.pending.extend(samples.iter().map(|s| s.to_f32()));
+                    return;
+                }
+                // 落后时已跳到最旧的可用数据，继续读取
+                Err(TapError::Lagged { .. }) => continue,
+                Err(_) => return,
+            }
+        }
+    }
+
+    /// 用最近一个分析块估计整体延迟（PHAT加权互相关）
+    fn estimate_delay(&mut self) {
+        let span = self.block + self.max_delay;
+        let reference = self.ref_history.window(span, 0);
+        let power = reference.iter().map(|x| x * x).sum::<f32>() / span as f32;
+        if power < MIN_REFERENCE_POWER {
+            return;
+        }
+
+        let mut ref_spectrum = Vec::new();
+        let mut mic_spectrum = Vec::new();
+        self.fft.forward_real(reference, &mut ref_spectrum);
+        self.fft.forward_real(self.mic_history.window(self.block, 0), &mut mic_spectrum);
+
+        // R[m] = Σ mic[i]·ref[i+m]，对应延迟 max_delay - m
+        let cross: Vec<Complex> = mic_spectrum
+            .iter()
+            .zip(ref_spectrum.iter())
+            .map(|(m, r)| {
+                let product = m.conj() * *r;
+                product.scale(1.0 / (product.norm() + 1e-9))
+            })
+            .collect();
+        let mut correlation = Vec::new();
+        self.fft.inverse_real(&cross, &mut correlation);
+
+        let lags = &correlation[..=self.max_delay];
+        let mean = lags.iter().map(|c| c.abs()).sum::<f32>() / lags.len() as f32;
+        let (best, peak) = lags
+            .iter()
+            .enumerate()
+            .fold((0, 0.0f32), |acc, (m, &c)| if c > acc.1 { (m, c) } else { acc });
+        if peak < mean * PEAK_RATIO {
+            return;
+        }
+
+        // 留出 1/8 滤波器长度的余量，容纳声学路径的前沿和延迟抖动
+        let estimate = (self.max_delay - best).saturating_sub(self.filter_len / 8);
+        let tolerance = self.filter_len / 4;
+        let close = |a: usize, b: usize| a.abs_diff(b) <= tolerance;
+        match self.delay {
+            None => self.set_delay(estimate),
+            Some(current) if close(current, estimate) => self.candidate = None,
+            // 已有延迟时需要连续两次一致的估计才切换，避免偶发的错误峰值打乱滤波器
+            Some(_) => match self.candidate {
+                Some(candidate) if close(candidate, estimate) => self.set_delay(estimate),
+                _ => self.candidate = Some(estimate),
+            },
+        }
+    }
+
+    fn set_delay(&mut self, delay: usize) {
+        self.delay = Some(delay);
+        self.candidate = None;
+        self.weights.fill(0.0);
+    }
+}
+
+impl AudioProcessor for EchoCanceller {
+    fn name(&self) -> &str {
+        "echo_cancellation"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        self.pull_reference();
+
+        for sample in samples.iter_mut() {
+            let mic = *sample;
+            let reference = self.pending.pop_front().unwrap_or(0.0);
+            self.ref_history.push(reference);
+            self.mic_history.push(mic);
+
+            self.since_estimate += 1;
+            if self.since_estimate >= self.block {
+                self.since_estimate = 0;
+                self.estimate_delay();
+            }
+
+            let Some(delay) = self.delay else {
+                continue;
+            };
+
+            let window = self.ref_history.window(self.filter_len, delay);
+            let mut estimate = 0.0;
+            let mut energy = 0.0;
+            for (&w, &x) in self.weights.iter().zip(window.iter()) {
+                estimate += w * x;
+                energy += x * x;
+            }
+            let error = mic - estimate;
+
+            // 回声经过外放到麦克风的路径总有衰减，麦克风明显比参考信号响时说明近端在说话
+            self.near_power += (mic * mic - self.near_power) * self.detector_coeff;
+            let reference_power = energy / self.filter_len as f32;
+            if self.near_power > DOUBLE_TALK_RATIO * DOUBLE_TALK_RATIO * reference_power {
+                self.double_talk_samples = self.double_talk_hold;
+            } else if self.double_talk_samples > 0 {
+                self.double_talk_samples -= 1;
+            }
+            // 只在远端有声音且没有双讲时自适应，近端语音不会把滤波器带偏
+            if self.double_talk_samples == 0 && energy > MIN_REFERENCE_POWER * self.filter_len as f32 {
+                let step = self.config.step_size * error / (energy + REGULARIZATION);
+                for (w, &x) in self.weights.iter_mut().zip(window.iter()) {
+                    *w += step * x;
+                }
+            }
+
+            self.mic_power += (mic * mic - self.mic_power) * self.erle_coeff;
+            self.residual_power += (error * error - self.residual_power) * self.erle_coeff;
+            *sample = error;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.ref_history.clear();
+        self.mic_history.clear();
+        self.weights.fill(0.0);
+        self.delay = None;
+        self.candidate = None;
+        self.since_estimate = 0;
+        self.double_talk_samples = 0;
+        self.near_power = 0.0;
+        self.mic_power = 0.0;
+        self.residual_power = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rng::tests::white_noise;
+    use crate::core::tap_buffer::{LagPolicy, TapBuffer};
+
+    const RATE: usize = 16000;
+
+    /// 模拟笔记本外放：整体延迟 + 短房间响应
+    fn echo_of(reference: &[f32], delay: usize) -> Vec<f32> {
+        let response = [(0, 0.3), (7, 0.15), (23, -0.1), (61, 0.05)];
+        (0..reference.len())
+            .map(|n| {
+                response
+                    .iter()
+                    .filter(|&&(offset, _)| n >= delay + offset)
+                    .map(|&(offset, gain)| reference[n - delay - offset] * gain)
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_echo_removed_after_delay_estimation() {
+        let tap = TapBuffer::new(RATE * 8);
+        let mut canceller = EchoCanceller::new(RATE as u32, tap.attach(LagPolicy::SkipForward));
+
+        // 耳机播放的翻译语音整段入队，回声在 120ms 后到达麦克风
+        let played = white_noise(RATE * 6, 0.3, 7);
+        tap.write(&played.iter().map(|&x| AudioSample::from_f32(x)).collect::<Vec<_>>());
+        let quantized: Vec<f32> = played.iter().map(|&x| AudioSample::from_f32(x).to_f32()).collect();
+        let room_noise = white_noise(RATE * 6, 0.001, 99);
+        let mut mic: Vec<f32> = echo_of(&quantized, 1920)
+            .iter()
+            .zip(room_noise.iter())
+            .map(|(e, n)| e + n)
+            .collect();
+        let original = mic.clone();
+
+        for chunk in mic.chunks_mut(480) {
+            canceller.process(chunk);
+        }
+
+        let delay = canceller.estimated_delay().expect("delay not estimated");
+        assert!(delay <= 1920 && 1920 - delay <= canceller.filter_len / 4, "delay {}", delay);
+
+        let tail = RATE * 4..RATE * 6;
+        let power = |s: &[f32]| s.iter().map(|x| x * x).sum::<f32>();
+        let erle = 10.0 * (power(&original[tail.clone()]) / power(&mic[tail])).log10();
+        assert!(erle > 20.0, "ERLE {:.1} dB", erle);
+        assert!(canceller.erle_db() > 20.0);
+    }
+
+    #[test]
+    fn test_near_end_passes_without_reference() {
+        let tap = TapBuffer::new(RATE);
+        let mut canceller = EchoCanceller::new(RATE as u32, tap.attach(LagPolicy::SkipForward));
+
+        // 没有播放任何音频：近端语音原样通过
+        let speech = white_noise(RATE * 2, 0.2, 3);
+        let mut mic = speech.clone();
+        canceller.process(&mut mic);
+        assert_eq!(mic, speech);
+        assert_eq!(canceller.estimated_delay(), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! 快速傅里叶变换模块
+//! 基2迭代FFT，预先计算旋转因子和位反转表，供频域处理（降噪、频谱分析、特征提取）使用
+
+use std::ops::{Add, Mul, Sub};
+
+/// 复数
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    /// 创建复数
+    pub const fn new(re: f32, im: f32) -> Self {
+        Complex { re, im }
+    }
+
+    /// 模的平方
+    pub fn norm_sqr(self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// 模
+    pub fn norm(self) -> f32 {
+        self.norm_sqr().sqrt()
+    }
+
+    /// 共轭
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// 乘以实数
+    pub fn scale(self, factor: f32) -> Self {
+        Complex::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// 固定长度的FFT（长度必须是2的幂）
+pub struct Fft {
+    size: usize,
+    twiddles: Vec<Complex>,  // e^{-2πik/N}, k < N/2
+    bit_reverse: Vec<usize>,
+}
+
+impl Fft {
+    /// 创建指定长度的FFT
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two() && size >= 2, "FFT size must be a power of two");
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = -2.0 * std::f64::consts::PI * k as f64 / size as f64;
+                Complex::new(angle.cos() as f32, angle.sin() as f32)
+            })
+            .collect();
+        let bits = size.trailing_zeros();
+        let bit_reverse = (0..size)
+            .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+
+        Fft { size, twiddles, bit_reverse }
+    }
+
+    /// 变换长度
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 原地正变换
+    pub fn forward(&self, data: &mut [Complex]) {
+        self.transform(data, false);
+    }
+
+    /// 原地逆变换（已除以N）
+    pub fn inverse(&self, data: &mut [Complex]) {
+        self.transform(data, true);
+        let scale = 1.0 / self.size as f32;
+        for value in data.iter_mut() {
+            *value = value.scale(scale);
+        }
+    }
+
+    /// 实信号正变换，返回 N/2+1 个频点
+    pub fn forward_real(&self, input: &[f32], spectrum: &mut Vec<Complex>) {
+        let mut buffer: Vec<Complex> = input.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        buffer.resize(self.size, Complex::default());
+        self.forward(&mut buffer);
+        spectrum.clear();
+        spectrum.extend_from_slice(&buffer[..self.size / 2 + 1]);
+    }
+
+    /// 由 N/2+1 个频点（共轭对称的一半）还原实信号
+    pub fn inverse_real(&self, spectrum: &[Complex], output: &mut Vec<f32>) {
+        let half = self.size / 2;
+        let mut buffer = vec![Complex::default(); self.size];
+        buffer[..=half].copy_from_slice(&spectrum[..=half]);
+        for k in 1..half {
+            buffer[self.size - k] = spectrum[k].conj();
+        }
+        self.inverse(&mut buffer);
+        output.clear();
+        output.extend(buffer.iter().map(|c| c.re));
+    }
+
+    fn transform(&self, data: &mut [Complex], inverse: bool) {
+        assert_eq!(data.len(), self.size, "FFT input length mismatch");
+        for i in 0..self.size {
+            let j = self.bit_reverse[i];
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+
+        let mut length = 2;
+        while length <= self.size {
+            let half = length / 2;
+            let stride = self.size / length;
+            for start in (0..self.size).step_by(length) {
+                for k in 0..half {
+                    let mut twiddle = self.twiddles[k * stride];
+                    if inverse {
+                        twiddle = twiddle.conj();
+                    }
+                    let even = data[start + k];
+                    let odd = data[start + k + half] * twiddle;
+                    data[start + k] = even + odd;
+                    data[start + k + half] = even - odd;
+                }
+            }
+            length *= 2;
+        }
+    }
+}
+
+/// 周期Hann窗
+pub fn hann_window(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft_matches_dft_and_round_trips() {
+        let size = 64;
+        let input: Vec<f32> = (0..size).map(|i| ((i * 7 % 13) as f32 - 6.0) / 6.0).collect();
+        let fft = Fft::new(size);
+
+        let mut spectrum = Vec::new();
+        fft.forward_real(&input, &mut spectrum);
+        assert_eq!(spectrum.len(), size / 2 + 1);
+
+        // 与直接DFT对比
+        for (k, bin) in spectrum.iter().enumerate() {
+            let mut expected = Complex::default();
+            for (n, &x) in input.iter().enumerate() {
+                let angle = -2.0 * std::f32::consts::PI * (k * n) as f32 / size as f32;
+                expected = expected + Complex::new(angle.cos(), angle.sin()).scale(x);
+            }
+            assert!((*bin - expected).norm() < 1e-3, "bin {}", k);
+        }
+
+        let mut output = Vec::new();
+        fft.inverse_real(&spectrum, &mut output);
+        for (a, b) in input.iter().zip(output.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
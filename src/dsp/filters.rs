@@ -0,0 +1,294 @@
+//! 基础处理级模块
+//! 去直流、高通、增益和前视限幅器，实现 AudioProcessor 接口
+
+use std::collections::VecDeque;
+use crate::dsp::processor::AudioProcessor;
+
+/// dB 转线性幅度
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// 线性幅度转 dB
+pub fn linear_to_db(linear: f32) -> f32 {
+    20.0 * linear.max(1e-10).log10()
+}
+
+/// 一阶去直流滤波器：y[n] = x[n] - x[n-1] + r * y[n-1]
+pub struct DcBlocker {
+    r: f32,
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl DcBlocker {
+    /// 创建去直流滤波器（截止频率约 10Hz）
+    pub fn new(sample_rate: u32) -> Self {
+        let cutoff = 10.0;
+        DcBlocker {
+            r: 1.0 - 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32,
+            prev_input: 0.0,
+            prev_output: 0.0,
+        }
+    }
+}
+
+impl AudioProcessor for DcBlocker {
+    fn name(&self) -> &str {
+        "dc_removal"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let output = *sample - self.prev_input + self.r * self.prev_output;
+            self.prev_input = *sample;
+            self.prev_output = output;
+            *sample = output;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.prev_input = 0.0;
+        self.prev_output = 0.0;
+    }
+}
+
+/// 二阶巴特沃斯高通滤波器（RBJ双二阶，直接II型转置）
+pub struct HighPass {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl HighPass {
+    /// 创建指定截止频率的高通滤波器
+    pub fn new(sample_rate: u32, cutoff_hz: f32) -> Self {
+        let cutoff = cutoff_hz.clamp(1.0, sample_rate as f32 * 0.45);
+        let omega = 2.0 * std::f32::consts::PI * cutoff / sample_rate as f32;
+        let alpha = omega.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+
+        HighPass {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+}
+
+impl AudioProcessor for HighPass {
+    fn name(&self) -> &str {
+        "high_pass"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let input = *sample;
+            let output = self.b0 * input + self.z1;
+            self.z1 = self.b1 * input - self.a1 * output + self.z2;
+            self.z2 = self.b2 * input - self.a2 * output;
+            *sample = output;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+/// 固定增益
+pub struct Gain {
+    gain: f32,
+}
+
+impl Gain {
+    /// 使用线性增益创建
+    pub fn new(gain: f32) -> Self {
+        Gain { gain }
+    }
+
+    /// 使用 dB 增益创建
+    pub fn from_db(gain_db: f32) -> Self {
+        Gain::new(db_to_linear(gain_db))
+    }
+
+    /// 修改增益（线性）
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+}
+
+impl AudioProcessor for Gain {
+    fn name(&self) -> &str {
+        "gain"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample *= self.gain;
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// 前视峰值限幅器
+///
+/// 输出延迟 `lookahead` 个采样，增益在峰值到达之前就已降到位，
+/// 保证输出不超过阈值；峰值过后按释放时间常数恢复。
+pub struct Limiter {
+    threshold: f32,
+    release_coeff: f32,
+    lookahead: usize,
+    delay: VecDeque<f32>,
+    required: VecDeque<(usize, f32)>,  // 单调队列：(位置, 所需增益)，用于求前视窗口内的最小增益
+    position: usize,
+    gain: f32,
+}
+
+impl Limiter {
+    /// 创建限幅器
+    pub fn new(sample_rate: u32, threshold_db: f32, lookahead_ms: f32, release_ms: f32) -> Self {
+        let lookahead = (sample_rate as f32 * lookahead_ms.max(0.0) / 1000.0).round() as usize;
+        let release_samples = (sample_rate as f32 * release_ms.max(0.1) / 1000.0).max(1.0);
+        Limiter {
+            threshold: db_to_linear(threshold_db),
+            release_coeff: (-1.0 / release_samples).exp(),
+            lookahead,
+            delay: VecDeque::with_capacity(lookahead + 1),
+            required: VecDeque::with_capacity(lookahead + 1),
+            position: 0,
+            gain: 1.0,
+        }
+    }
+
+    /// 当前增益（线性，1.0 表示未压缩）
+    pub fn current_gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// 处理单个采样，`peak` 为该采样对应的峰值（采样峰值或真峰值）
+    pub(crate) fn limit(&mut self, input: f32, peak: f32) -> f32 {
+        let needed = if peak > self.threshold { self.threshold / peak } else { 1.0 };
+
+        // 维护窗口 [position - lookahead, position] 内所需增益的最小值
+        while matches!(self.required.back(), Some(&(_, g)) if g >= needed) {
+            self.required.pop_back();
+        }
+        self.required.push_back((self.position, needed));
+        while matches!(self.required.front(), Some(&(p, _)) if p + self.lookahead < self.position) {
+            self.required.pop_front();
+        }
+        let target = self.required.front().map(|&(_, g)| g).unwrap_or(1.0);
+
+        // 瞬时启动，指数释放
+        self.gain = if target < self.gain {
+            target
+        } else {
+            target + (self.gain - target) * self.release_coeff
+        };
+
+        self.delay.push_back(input);
+        let delayed = if self.delay.len() > self.lookahead {
+            self.delay.pop_front().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        self.position += 1;
+        delayed * self.gain
+    }
+}
+
+impl AudioProcessor for Limiter {
+    fn name(&self) -> &str {
+        "limiter"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.limit(*sample, sample.abs());
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.lookahead
+    }
+
+    fn gain_db(&self) -> Option<f32> {
+        Some(linear_to_db(self.gain))
+    }
+
+    fn reset(&mut self) {
+        self.delay.clear();
+        self.required.clear();
+        self.position = 0;
+        self.gain = 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32, amplitude: f32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / 16000.0).sin() * amplitude)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_dc_removal_and_high_pass() {
+        // 带0.3直流偏置的1kHz信号：去直流后均值接近0，信号保留
+        let mut signal: Vec<f32> = tone(1000.0, 0.2, 16000).iter().map(|s| s + 0.3).collect();
+        DcBlocker::new(16000).process(&mut signal);
+        let tail = &signal[8000..];
+        assert!((tail.iter().sum::<f32>() / tail.len() as f32).abs() < 0.005);
+        assert!((rms(tail) - 0.2 / 2f32.sqrt()).abs() < 0.01);
+
+        // 80Hz高通：30Hz嗡声被衰减，1kHz语音频段通过
+        let mut hum = tone(30.0, 0.5, 16000);
+        let mut voice = tone(1000.0, 0.5, 16000);
+        let mut filter = HighPass::new(16000, 80.0);
+        filter.process(&mut hum);
+        filter.reset();
+        filter.process(&mut voice);
+        assert!(rms(&hum[4000..]) < 0.5 / 2f32.sqrt() * 0.2);
+        assert!((rms(&voice[4000..]) - 0.5 / 2f32.sqrt()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_limiter_holds_threshold() {
+        let mut limiter = Limiter::new(16000, -6.0, 2.0, 50.0);
+        let threshold = db_to_linear(-6.0);
+
+        let mut signal = tone(440.0, 0.25, 1600);
+        signal.extend(tone(440.0, 1.0, 1600));
+        limiter.process(&mut signal);
+
+        // 前视保证峰值不超过阈值
+        assert!(signal.iter().all(|s| s.abs() <= threshold + 1e-6));
+        assert!(limiter.current_gain() < 0.6);
+        // 低于阈值的部分原样通过（延迟 lookahead 个采样）
+        let expected = tone(440.0, 0.25, 1600);
+        assert!((signal[100 + limiter.latency_samples()] - expected[100]).abs() < 1e-6);
+
+        let mut gain = Gain::from_db(-6.0);
+        let mut half = vec![1.0f32];
+        gain.process(&mut half);
+        assert!((half[0] - threshold).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,316 @@
+//! 电平表模块
+//! 按固定间隔统计 RMS、峰值、削波次数，可选按对数频带统计频谱能量，供界面和诊断使用
+
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::Sample;
+use crate::dsp::fft::{hann_window, Complex, Fft};
+use crate::dsp::filters::{db_to_linear, linear_to_db};
+
+/// 电平表参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelMeterConfig {
+    pub update_ms: u64,          // 发布间隔（按音频时长计）
+    pub clip_level_db: f32,      // 达到该电平的采样计为削波
+    pub spectrum_bands: usize,   // 频带数，0 表示不做频谱分析
+    pub fft_size: usize,         // 频谱分析的FFT长度（2的幂）
+    pub min_band_hz: f32,        // 最低频带的下边界，频带按对数间隔划分到奈奎斯特频率
+}
+
+impl Default for LevelMeterConfig {
+    fn default() -> Self {
+        LevelMeterConfig {
+            update_ms: 100,
+            clip_level_db: -0.1,
+            spectrum_bands: 0,
+            fft_size: 1024,
+            min_band_hz: 100.0,
+        }
+    }
+}
+
+/// 一个频带的能量
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandLevel {
+    pub low_hz: f32,
+    pub high_hz: f32,
+    pub level_db: f32,  // 频带内的均方能量（dBFS，与 rms_db 同一标度）
+}
+
+/// 一次电平读数
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelReading {
+    pub rms_db: f32,
+    pub peak_db: f32,
+    pub clip_count: u64,        // 本间隔内的削波采样数
+    pub bands: Vec<BandLevel>,  // 未启用频谱分析时为空
+    pub sample_position: u64,   // 本间隔结束时已统计的采样总数
+}
+
+impl Default for LevelReading {
+    fn default() -> Self {
+        LevelReading {
+            rms_db: linear_to_db(0.0),
+            peak_db: linear_to_db(0.0),
+            clip_count: 0,
+            bands: Vec::new(),
+            sample_position: 0,
+        }
+    }
+}
+
+/// 频谱分析：不重叠的加窗帧功率谱在发布间隔内取平均
+struct Spectrum {
+    fft: Fft,
+    window: Vec<f32>,
+    scale: f32,                        // 功率谱到均方能量的换算系数
+    bands: Vec<(f32, f32, usize, usize)>,  // (下边界Hz, 上边界Hz, 起始bin, 结束bin)
+    frame: Vec<f32>,
+    spectrum: Vec<Complex>,
+    band_power: Vec<f32>,
+    frames: usize,
+}
+
+impl Spectrum {
+    fn new(sample_rate: u32, config: &LevelMeterConfig) -> Self {
+        let size = config.fft_size.next_power_of_two().max(64);
+        let window = hann_window(size);
+        let window_energy: f32 = window.iter().map(|w| w * w).sum();
+        let bin_hz = sample_rate as f32 / size as f32;
+        let nyquist = sample_rate as f32 / 2.0;
+        let low = config.min_band_hz.clamp(bin_hz, nyquist / 2.0);
+        let count = config.spectrum_bands;
+
+        let bands = (0..count)
+            .map(|i| {
+                let edge = |k: usize| low * (nyquist / low).powf(k as f32 / count as f32);
+                let (low_hz, high_hz) = (edge(i), edge(i + 1));
+                let start = (low_hz / bin_hz).ceil() as usize;
+                let end = ((high_hz / bin_hz).ceil() as usize).max(start + 1).min(size / 2 + 1);
+                (low_hz, high_hz, start.min(end - 1), end)
+            })
+            .collect();
+
+        Spectrum {
+            fft: Fft::new(size),
+            window,
+            scale: 2.0 / (size as f32 * window_energy),
+            bands,
+            frame: Vec::with_capacity(size),
+            spectrum: Vec::new(),
+            band_power: vec![0.0; count],
+            frames: 0,
+        }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.frame.push(sample);
+        if self.frame.len() < self.fft.size() {
+            return;
+        }
+
+        for (value, &w) in self.frame.iter_mut().zip(self.window.iter()) {
+            *value *= w;
+        }
+        self.fft.forward_real(&self.frame, &mut self.spectrum);
+        for (power, &(_, _, start, end)) in self.band_power.iter_mut().zip(self.bands.iter()) {
+            *power += self.spectrum[start..end].iter().map(|c| c.norm_sqr()).sum::<f32>() * self.scale;
+        }
+        self.frames += 1;
+        self.frame.clear();
+    }
+
+    /// 取出本间隔的平均频带能量；间隔内没有完整的帧时沿用上次的能量
+    fn take(&mut self, previous: &[BandLevel]) -> Vec<BandLevel> {
+        if self.frames == 0 && !previous.is_empty() {
+            return previous.to_vec();
+        }
+        let frames = self.frames.max(1) as f32;
+        let levels = self.bands.iter()
+            .zip(self.band_power.iter())
+            .map(|(&(low_hz, high_hz, _, _), &power)| BandLevel {
+                low_hz,
+                high_hz,
+                level_db: 10.0 * (power / frames).max(1e-20).log10(),
+            })
+            .collect();
+        self.band_power.iter_mut().for_each(|p| *p = 0.0);
+        self.frames = 0;
+        levels
+    }
+}
+
+/// 电平表
+///
+/// 按采样数而不是墙钟计时：每累计 `update_ms` 的音频发布一次读数，
+/// 因此读数速率与音频流速率严格一致。
+pub struct LevelMeter {
+    config: LevelMeterConfig,
+    sample_rate: u32,
+    interval: usize,
+    clip_level: f32,
+    spectrum: Option<Spectrum>,
+    sum_squares: f64,
+    peak: f32,
+    clips: u64,
+    count: usize,
+    position: u64,
+    total_clips: u64,
+    last: LevelReading,
+}
+
+impl LevelMeter {
+    /// 使用默认参数创建（不做频谱分析）
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_config(sample_rate, LevelMeterConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, config: LevelMeterConfig) -> Self {
+        let interval = ((sample_rate as u64 * config.update_ms.max(1)) / 1000).max(1) as usize;
+        LevelMeter {
+            sample_rate,
+            interval,
+            clip_level: db_to_linear(config.clip_level_db),
+            spectrum: (config.spectrum_bands > 0).then(|| Spectrum::new(sample_rate, &config)),
+            sum_squares: 0.0,
+            peak: 0.0,
+            clips: 0,
+            count: 0,
+            position: 0,
+            total_clips: 0,
+            last: LevelReading::default(),
+            config,
+        }
+    }
+
+    /// 电平表参数
+    pub fn config(&self) -> &LevelMeterConfig {
+        &self.config
+    }
+
+    /// 每次发布读数对应的采样数
+    pub fn interval_samples(&self) -> usize {
+        self.interval
+    }
+
+    /// 最近一次读数
+    pub fn last_reading(&self) -> &LevelReading {
+        &self.last
+    }
+
+    /// 累计削波采样数
+    pub fn total_clips(&self) -> u64 {
+        self.total_clips
+    }
+
+    /// 统计一段音频，返回其间完成的读数（可能为空或多个）
+    pub fn push(&mut self, samples: &[f32]) -> Vec<LevelReading> {
+        let mut readings = Vec::new();
+        for &sample in samples {
+            let magnitude = sample.abs();
+            self.sum_squares += (sample as f64) * (sample as f64);
+            self.peak = self.peak.max(magnitude);
+            if magnitude >= self.clip_level {
+                self.clips += 1;
+            }
+            if let Some(ref mut spectrum) = self.spectrum {
+                spectrum.push(sample);
+            }
+            self.count += 1;
+            self.position += 1;
+
+            if self.count == self.interval {
+                readings.push(self.publish());
+            }
+        }
+        readings
+    }
+
+    /// 统计流水线采样
+    pub fn push_audio(&mut self, samples: &[AudioSample]) -> Vec<LevelReading> {
+        let samples: Vec<f32> = samples.iter().map(|s| s.to_f32()).collect();
+        self.push(&samples)
+    }
+
+    /// 清空统计
+    pub fn reset(&mut self) {
+        *self = Self::with_config(self.sample_rate, self.config.clone());
+    }
+
+    fn publish(&mut self) -> LevelReading {
+        let rms = (self.sum_squares / self.count as f64).sqrt() as f32;
+        let bands = match self.spectrum {
+            Some(ref mut spectrum) => spectrum.take(&self.last.bands),
+            None => Vec::new(),
+        };
+        let reading = LevelReading {
+            rms_db: linear_to_db(rms),
+            peak_db: linear_to_db(self.peak),
+            clip_count: self.clips,
+            bands,
+            sample_position: self.position,
+        };
+
+        self.total_clips += self.clips;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.clips = 0;
+        self.count = 0;
+        self.last = reading.clone();
+        reading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    #[test]
+    fn test_level_and_spectrum_readings() {
+        let config = LevelMeterConfig { spectrum_bands: 8, ..Default::default() };
+        let mut meter = LevelMeter::with_config(RATE, config);
+        assert_eq!(meter.interval_samples(), 1600);
+
+        // 1秒 1kHz 正弦（幅度0.5）：每100ms一个读数
+        let tone: Vec<f32> = (0..RATE as usize)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / RATE as f32).sin())
+            .collect();
+        let mut readings = Vec::new();
+        for chunk in tone.chunks(300) {
+            readings.extend(meter.push(chunk));
+        }
+        assert_eq!(readings.len(), 10);
+        assert_eq!(readings[9].sample_position, 16000);
+
+        let reading = &readings[5];
+        assert!((reading.rms_db - (-9.03)).abs() < 0.1, "rms {}", reading.rms_db);
+        assert!((reading.peak_db - (-6.02)).abs() < 0.1, "peak {}", reading.peak_db);
+        assert_eq!(reading.clip_count, 0);
+
+        // 能量集中在包含1kHz的频带，且与 RMS 同一标度
+        assert_eq!(reading.bands.len(), 8);
+        let band = reading.bands.iter().find(|b| b.low_hz <= 1000.0 && b.high_hz > 1000.0).unwrap();
+        assert!((band.level_db - reading.rms_db).abs() < 1.0, "band {}", band.level_db);
+        for other in reading.bands.iter().filter(|b| b.high_hz < 700.0 || b.low_hz > 1500.0) {
+            assert!(other.level_db < band.level_db - 30.0, "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn test_clipping_counted() {
+        let mut meter = LevelMeter::new(RATE);
+        let mut audio = vec![1000i16; 1600];
+        audio[10] = i16::MAX;
+        audio[20] = i16::MIN;
+        let readings = meter.push_audio(&audio);
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].clip_count, 2);
+        assert!(readings[0].peak_db > -0.01);
+        assert!(readings[0].bands.is_empty());
+        assert_eq!(meter.total_clips(), 2);
+    }
+}
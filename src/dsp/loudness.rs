@@ -0,0 +1,398 @@
+//! 响度归一化模块
+//! 按 ITU-R BS.1770 / EBU R128 测量积分响度（LUFS），把合成语音调整到目标响度，并用真峰值限幅器防止过载
+
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::{Ditherer, Sample};
+use crate::dsp::filters::{db_to_linear, linear_to_db, Limiter};
+use crate::dsp::processor::AudioProcessor;
+
+const BLOCK_MS: u32 = 400;            // 门限块长度
+const BLOCK_STEP_MS: u32 = 100;       // 门限块步长（75%重叠）
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+const OVERSAMPLING: usize = 4;        // 真峰值检测的过采样倍数
+const INTERPOLATION_TAPS: usize = 12; // 每个相位的插值滤波器长度
+const LIMITER_LOOKAHEAD_MS: f32 = 1.5;
+const LIMITER_RELEASE_MS: f32 = 50.0;
+
+/// 响度归一化参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessConfig {
+    pub target_lufs: f32,    // 目标积分响度（EBU R128 为 -23 LUFS）
+    pub true_peak_db: f32,   // 真峰值上限（dBTP）
+    pub max_gain_db: f32,    // 最大提升量，避免把几乎无声的片段放大成噪声
+}
+
+impl Default for LoudnessConfig {
+    fn default() -> Self {
+        LoudnessConfig {
+            target_lufs: -23.0,
+            true_peak_db: -1.0,
+            max_gain_db: 24.0,
+        }
+    }
+}
+
+/// 直接II型转置双二阶滤波器
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.z[0];
+        self.z[0] = self.b[1] * input - self.a[0] * output + self.z[1];
+        self.z[1] = self.b[2] * input - self.a[1] * output;
+        output
+    }
+}
+
+/// BS.1770 K加权滤波器：头部效应高架滤波 + RLB高通，系数按采样率计算
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate as f64;
+
+        let f0 = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        KWeighting { shelf, high_pass }
+    }
+
+    fn process(&mut self, input: f32) -> f64 {
+        self.high_pass.process(self.shelf.process(input as f64))
+    }
+}
+
+/// 积分响度测量（单声道）
+pub struct LoudnessMeter {
+    weighting: KWeighting,
+    block_len: usize,
+    step_len: usize,
+    steps: Vec<f64>,       // 每个100ms步长内K加权信号的能量和
+    current: f64,
+    current_len: usize,
+    total_len: usize,      // 已输入的采样总数
+}
+
+impl LoudnessMeter {
+    /// 创建响度表
+    pub fn new(sample_rate: u32) -> Self {
+        let step_len = (sample_rate * BLOCK_STEP_MS / 1000) as usize;
+        LoudnessMeter {
+            weighting: KWeighting::new(sample_rate),
+            block_len: step_len * (BLOCK_MS / BLOCK_STEP_MS) as usize,
+            step_len,
+            steps: Vec::new(),
+            current: 0.0,
+            current_len: 0,
+            total_len: 0,
+        }
+    }
+
+    /// 输入一段采样
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let weighted = self.weighting.process(sample);
+            self.current += weighted * weighted;
+            self.current_len += 1;
+            if self.current_len == self.step_len {
+                self.steps.push(self.current);
+                self.current = 0.0;
+                self.current_len = 0;
+            }
+        }
+        self.total_len += samples.len();
+    }
+
+    /// 门限积分响度（LUFS），全部低于绝对门限（静音）时返回 None
+    ///
+    /// 不足一个400ms门限块的短片段按整段能量计算。
+    pub fn integrated_lufs(&self) -> Option<f32> {
+        let per_block = (self.block_len / self.step_len).max(1);
+        let mut blocks: Vec<f64> = self
+            .steps
+            .windows(per_block)
+            .map(|window| window.iter().sum::<f64>() / self.block_len as f64)
+            .collect();
+        if blocks.is_empty() && self.total_len > 0 {
+            let total = self.steps.iter().sum::<f64>() + self.current;
+            blocks.push(total / self.total_len as f64);
+        }
+
+        let loudness = |power: f64| -0.691 + 10.0 * power.max(1e-20).log10();
+        let gated_mean = |threshold: f64| {
+            let passed: Vec<f64> = blocks.iter().copied().filter(|&p| loudness(p) > threshold).collect();
+            (!passed.is_empty()).then(|| passed.iter().sum::<f64>() / passed.len() as f64)
+        };
+
+        let absolute = gated_mean(ABSOLUTE_GATE_LUFS)?;
+        let relative = gated_mean(loudness(absolute) + RELATIVE_GATE_LU)?;
+        Some(loudness(relative) as f32)
+    }
+
+    /// 清空测量结果
+    pub fn reset(&mut self) {
+        self.steps.clear();
+        self.current = 0.0;
+        self.current_len = 0;
+        self.total_len = 0;
+        self.weighting.shelf.z = [0.0; 2];
+        self.weighting.high_pass.z = [0.0; 2];
+    }
+}
+
+/// 真峰值检测：4倍过采样插值，估计采样点之间的峰值
+struct TruePeakDetector {
+    coeffs: Vec<[f32; INTERPOLATION_TAPS]>, // 每个分数位置的插值系数
+    history: [f32; INTERPOLATION_TAPS],
+}
+
+impl TruePeakDetector {
+    fn new() -> Self {
+        let half = INTERPOLATION_TAPS as f32 / 2.0;
+        let mut coeffs = Vec::new();
+        // 中心采样前后各 1/4、2/4、3/4 个采样间隔的位置
+        for phase in 1..OVERSAMPLING {
+            for direction in [-1.0f32, 1.0] {
+                let offset = direction * phase as f32 / OVERSAMPLING as f32;
+                let mut taps = [0.0; INTERPOLATION_TAPS];
+                for (j, tap) in taps.iter_mut().enumerate() {
+                    // 第 j 个历史采样相对中心的位置为 j - (half - 1)
+                    let t = offset - (j as f32 - (half - 1.0));
+                    let sinc = if t.abs() < 1e-6 {
+                        1.0
+                    } else {
+                        (std::f32::consts::PI * t).sin() / (std::f32::consts::PI * t)
+                    };
+                    let window = 0.5 + 0.5 * (std::f32::consts::PI * t / (half + 0.5)).cos();
+                    *tap = sinc * window;
+                }
+                let sum: f32 = taps.iter().sum();
+                taps.iter_mut().for_each(|tap| *tap /= sum);
+                coeffs.push(taps);
+            }
+        }
+
+        TruePeakDetector { coeffs, history: [0.0; INTERPOLATION_TAPS] }
+    }
+
+    /// 延迟（采样数）：插值需要中心之后的采样
+    fn latency(&self) -> usize {
+        INTERPOLATION_TAPS / 2
+    }
+
+    /// 输入一个采样，返回延迟后的中心采样及其附近的真峰值
+    fn push(&mut self, sample: f32) -> (f32, f32) {
+        self.history.copy_within(1.., 0);
+        self.history[INTERPOLATION_TAPS - 1] = sample;
+
+        let center = self.history[INTERPOLATION_TAPS / 2 - 1];
+        let peak = self
+            .coeffs
+            .iter()
+            .map(|taps| taps.iter().zip(self.history.iter()).map(|(c, x)| c * x).sum::<f32>().abs())
+            .fold(center.abs(), f32::max);
+        (center, peak)
+    }
+
+    fn reset(&mut self) {
+        self.history = [0.0; INTERPOLATION_TAPS];
+    }
+}
+
+/// 一段音频的真峰值（线性幅度）
+pub fn true_peak(samples: &[f32]) -> f32 {
+    let mut detector = TruePeakDetector::new();
+    let latency = detector.latency();
+    samples
+        .iter()
+        .copied()
+        .chain(std::iter::repeat_n(0.0, latency))
+        .map(|sample| detector.push(sample).1)
+        .fold(0.0, f32::max)
+}
+
+/// 一段音频的真峰值（dBTP）
+pub fn true_peak_db(samples: &[f32]) -> f32 {
+    linear_to_db(true_peak(samples))
+}
+
+/// 真峰值限幅器：按过采样估计的峰值计算增益，保证输出的真峰值不超过阈值
+pub struct TruePeakLimiter {
+    detector: TruePeakDetector,
+    limiter: Limiter,
+}
+
+impl TruePeakLimiter {
+    /// 创建真峰值限幅器
+    pub fn new(sample_rate: u32, threshold_db: f32) -> Self {
+        TruePeakLimiter {
+            detector: TruePeakDetector::new(),
+            limiter: Limiter::new(sample_rate, threshold_db, LIMITER_LOOKAHEAD_MS, LIMITER_RELEASE_MS),
+        }
+    }
+}
+
+impl AudioProcessor for TruePeakLimiter {
+    fn name(&self) -> &str {
+        "true_peak_limiter"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let (center, peak) = self.detector.push(*sample);
+            *sample = self.limiter.limit(center, peak);
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.detector.latency() + self.limiter.latency_samples()
+    }
+
+    fn gain_db(&self) -> Option<f32> {
+        self.limiter.gain_db()
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+        self.limiter.reset();
+    }
+}
+
+/// 语音片段响度归一化器
+///
+/// 对完整的合成语音片段测量积分响度，施加静态增益使其达到目标响度，
+/// 再经真峰值限幅。输出与输入等长且对齐（限幅器延迟在片段内部补偿）。
+pub struct LoudnessNormalizer {
+    sample_rate: u32,
+    config: LoudnessConfig,
+}
+
+impl LoudnessNormalizer {
+    /// 使用默认参数（EBU R128：-23 LUFS，-1 dBTP）创建
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_config(sample_rate, LoudnessConfig::default())
+    }
+
+    /// 使用指定参数创建
+    pub fn with_config(sample_rate: u32, config: LoudnessConfig) -> Self {
+        LoudnessNormalizer { sample_rate, config }
+    }
+
+    /// 归一化参数
+    pub fn config(&self) -> &LoudnessConfig {
+        &self.config
+    }
+
+    /// 归一化一段浮点音频，返回施加的增益（dB）；静音片段不做处理，返回 None
+    pub fn normalize(&self, samples: &mut [f32]) -> Option<f32> {
+        let mut meter = LoudnessMeter::new(self.sample_rate);
+        meter.push(samples);
+        let measured = meter.integrated_lufs()?;
+        let gain_db = (self.config.target_lufs - measured).min(self.config.max_gain_db);
+        let gain = db_to_linear(gain_db);
+
+        let mut limiter = TruePeakLimiter::new(self.sample_rate, self.config.true_peak_db);
+        let latency = limiter.latency_samples();
+        let mut buffer: Vec<f32> = samples.iter().map(|s| s * gain).collect();
+        buffer.resize(samples.len() + latency, 0.0);
+        limiter.process(&mut buffer);
+        samples.copy_from_slice(&buffer[latency..]);
+        Some(gain_db)
+    }
+
+    /// 归一化流水线采样（带抖动量化）
+    pub fn normalize_audio(&self, samples: &mut [AudioSample]) -> Option<f32> {
+        let mut buffer: Vec<f32> = samples.iter().map(|s| s.to_f32()).collect();
+        let gain_db = self.normalize(&mut buffer)?;
+        let mut ditherer = Ditherer::new();
+        for (out, &value) in samples.iter_mut().zip(buffer.iter()) {
+            *out = ditherer.quantize(value);
+        }
+        Some(gain_db)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(frequency: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / RATE as f32).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn test_loudness_meter_reference_tone() {
+        // 1kHz正弦，峰值 -20dBFS：单声道响度约 -23 LUFS
+        let mut meter = LoudnessMeter::new(RATE);
+        meter.push(&tone(1000.0, 0.1, 3.0));
+        let lufs = meter.integrated_lufs().unwrap();
+        assert!((lufs + 23.0).abs() < 0.2, "{} LUFS", lufs);
+
+        // 后接的静音块被绝对门限排除，只有跨越结尾的几个块略微拉低积分响度
+        meter.push(&vec![0.0; RATE as usize * 3]);
+        assert!((meter.integrated_lufs().unwrap() - lufs).abs() < 0.5);
+        meter.reset();
+        meter.push(&vec![0.0; RATE as usize]);
+        assert_eq!(meter.integrated_lufs(), None);
+    }
+
+    #[test]
+    fn test_normalizer_hits_target_and_true_peak() {
+        let normalizer = LoudnessNormalizer::new(RATE);
+
+        // 不同“音色”的合成语音：轻声和接近满幅都被拉到同一响度
+        for (amplitude, frequency) in [(0.02, 300.0), (0.9, 3700.0)] {
+            let mut speech = tone(frequency, amplitude, 2.0);
+            let gain = normalizer.normalize(&mut speech).unwrap();
+
+            let mut meter = LoudnessMeter::new(RATE);
+            meter.push(&speech);
+            let lufs = meter.integrated_lufs().unwrap();
+            assert!((lufs + 23.0).abs() < 0.5, "amplitude {} gain {} -> {} LUFS", amplitude, gain, lufs);
+            assert!(true_peak_db(&speech) <= -1.0 + 0.2);
+        }
+
+        // 响度目标很高时由真峰值限幅器兜底：接近 fs/4 的正弦采样点之间的峰值也不超过上限
+        let loud = LoudnessNormalizer::with_config(RATE, LoudnessConfig { target_lufs: 0.0, ..Default::default() });
+        let mut speech = tone(3990.0, 0.5, 1.0);
+        let mut samples: Vec<AudioSample> = speech.iter().map(|&s| AudioSample::from_f32(s)).collect();
+        loud.normalize(&mut speech).unwrap();
+        assert!(true_peak_db(&speech) <= -1.0 + 0.2, "{} dBTP", true_peak_db(&speech));
+        assert!(loud.normalize_audio(&mut samples).is_some());
+        assert!(samples.iter().all(|&s| (s as f32).abs() <= 32768.0 * db_to_linear(-0.8)));
+    }
+}
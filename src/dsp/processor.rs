@@ -0,0 +1,234 @@
+//! 音频处理链模块
+//! 定义可组合的 AudioProcessor 接口，按顺序串联多个处理级组成 DspChain
+
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::{Ditherer, Sample};
+use crate::dsp::agc::{Agc, AgcConfig};
+use crate::dsp::filters::{DcBlocker, Gain, HighPass, Limiter};
+use crate::dsp::noise_suppressor::{NoiseSuppressor, NoiseSuppressorConfig};
+
+/// 音频处理级
+///
+/// 处理单声道浮点采样（[-1.0, 1.0)），原地修改，需要在多次调用之间保持内部状态。
+pub trait AudioProcessor: Send {
+    /// 处理级名称
+    fn name(&self) -> &str;
+
+    /// 原地处理一段采样
+    fn process(&mut self, samples: &mut [f32]);
+
+    /// 处理级引入的延迟（采样数）
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
+    /// 当前施加的增益（dB），供诊断显示；不调整增益的处理级返回 None
+    fn gain_db(&self) -> Option<f32> {
+        None
+    }
+
+    /// 清除内部状态（例如切换音频源之后）
+    fn reset(&mut self);
+}
+
+/// 处理级配置，可从配置文件反序列化后构建处理链
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DspStageConfig {
+    DcRemoval,
+    HighPass { cutoff_hz: f32 },
+    Gain { gain_db: f32 },
+    NoiseSuppression(NoiseSuppressorConfig),
+    Agc(AgcConfig),
+    Limiter { threshold_db: f32, lookahead_ms: f32, release_ms: f32 },
+}
+
+impl DspStageConfig {
+    /// 按配置创建处理级
+    pub fn build(&self, sample_rate: u32) -> Box<dyn AudioProcessor> {
+        match *self {
+            DspStageConfig::DcRemoval => Box::new(DcBlocker::new(sample_rate)),
+            DspStageConfig::HighPass { cutoff_hz } => Box::new(HighPass::new(sample_rate, cutoff_hz)),
+            DspStageConfig::Gain { gain_db } => Box::new(Gain::from_db(gain_db)),
+            DspStageConfig::NoiseSuppression(ref config) => {
+                Box::new(NoiseSuppressor::with_config(sample_rate, config.clone()))
+            }
+            DspStageConfig::Agc(ref config) => Box::new(Agc::with_config(sample_rate, config.clone())),
+            DspStageConfig::Limiter { threshold_db, lookahead_ms, release_ms } => {
+                Box::new(Limiter::new(sample_rate, threshold_db, lookahead_ms, release_ms))
+            }
+        }
+    }
+}
+
+/// 按顺序执行的音频处理链
+#[derive(Default)]
+pub struct DspChain {
+    stages: Vec<Box<dyn AudioProcessor>>,
+    scratch: Vec<f32>,     // 整数采样转换用的缓冲区
+    ditherer: Ditherer,
+}
+
+impl DspChain {
+    /// 创建空的处理链（直通）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按配置列表创建处理链
+    pub fn from_config(stages: &[DspStageConfig], sample_rate: u32) -> Self {
+        let mut chain = Self::new();
+        for stage in stages {
+            chain.push(stage.build(sample_rate));
+        }
+        chain
+    }
+
+    /// 语音前端的默认处理链：去直流、80Hz高通、自动增益、-1dBFS限幅
+    pub fn voice_default(sample_rate: u32) -> Self {
+        Self::from_config(
+            &[
+                DspStageConfig::DcRemoval,
+                DspStageConfig::HighPass { cutoff_hz: 80.0 },
+                DspStageConfig::Agc(AgcConfig::default()),
+                DspStageConfig::Limiter { threshold_db: -1.0, lookahead_ms: 1.5, release_ms: 50.0 },
+            ],
+            sample_rate,
+        )
+    }
+
+    /// 追加处理级（构建器风格）
+    pub fn with(mut self, stage: impl AudioProcessor + 'static) -> Self {
+        self.push(Box::new(stage));
+        self
+    }
+
+    /// 追加处理级
+    pub fn push(&mut self, stage: Box<dyn AudioProcessor>) {
+        self.stages.push(stage);
+    }
+
+    /// 在指定位置插入处理级
+    pub fn insert(&mut self, index: usize, stage: Box<dyn AudioProcessor>) {
+        self.stages.insert(index.min(self.stages.len()), stage);
+    }
+
+    /// 移除指定名称的处理级，返回被移除的处理级
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn AudioProcessor>> {
+        let index = self.position(name)?;
+        Some(self.stages.remove(index))
+    }
+
+    /// 指定名称的处理级所在位置
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.name() == name)
+    }
+
+    /// 处理级数量
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// 是否为空链
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// 各处理级名称（按执行顺序）
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// 指定名称的处理级当前增益（dB），没有该处理级时返回 None
+    pub fn stage_gain_db(&self, name: &str) -> Option<f32> {
+        self.stages
+            .iter()
+            .find(|stage| stage.name() == name)
+            .and_then(|stage| stage.gain_db())
+    }
+
+    /// 处理整数采样（内部转换为浮点处理，再带抖动量化回来）
+    pub fn process_samples<S: Sample>(&mut self, samples: &mut [S]) {
+        if self.stages.is_empty() {
+            return;
+        }
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.extend(samples.iter().map(|s| s.to_f32()));
+        self.process(&mut scratch);
+        for (out, &value) in samples.iter_mut().zip(scratch.iter()) {
+            *out = self.ditherer.quantize(value);
+        }
+        self.scratch = scratch;
+    }
+
+    /// 处理流水线采样
+    pub fn process_audio(&mut self, samples: &mut [AudioSample]) {
+        self.process_samples(samples);
+    }
+}
+
+impl AudioProcessor for DspChain {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for stage in self.stages.iter_mut() {
+            stage.process(samples);
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.stages.iter().map(|stage| stage.latency_samples()).sum()
+    }
+
+    fn reset(&mut self) {
+        for stage in self.stages.iter_mut() {
+            stage.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_from_config() {
+        let json = r#"[
+            {"type": "dc_removal"},
+            {"type": "high_pass", "cutoff_hz": 100.0},
+            {"type": "gain", "gain_db": 6.0},
+            {"type": "noise_suppression", "floor_db": -15.0},
+            {"type": "agc", "target_rms_db": -18.0},
+            {"type": "limiter", "threshold_db": -3.0, "lookahead_ms": 2.0, "release_ms": 50.0}
+        ]"#;
+        let config: Vec<DspStageConfig> = serde_json::from_str(json).unwrap();
+        let mut chain = DspChain::from_config(&config, 16000);
+
+        assert_eq!(
+            chain.stage_names(),
+            vec!["dc_removal", "high_pass", "gain", "noise_suppression", "agc", "limiter"]
+        );
+        assert_eq!(chain.stage_gain_db("agc"), Some(0.0));
+        assert_eq!(chain.stage_gain_db("high_pass"), None);
+        // 降噪一个分析帧（512）加前视限幅器 2ms @ 16kHz
+        assert_eq!(chain.latency_samples(), 512 + 32);
+
+        assert!(chain.remove("noise_suppression").is_some());
+        assert!(chain.remove("noise_suppression").is_none());
+        assert_eq!(chain.position("agc"), Some(3));
+        assert_eq!(chain.latency_samples(), 32);
+    }
+
+    #[test]
+    fn test_empty_chain_is_passthrough() {
+        let mut chain = DspChain::new();
+        let mut samples: Vec<AudioSample> = vec![1, -2, 300, i16::MIN];
+        chain.process_audio(&mut samples);
+        assert_eq!(samples, vec![1, -2, 300, i16::MIN]);
+        assert_eq!(chain.latency_samples(), 0);
+    }
+}
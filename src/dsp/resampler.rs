@@ -0,0 +1,288 @@
+//! 采样率转换模块
+//! 基于Kaiser窗sinc插值的多相滤波器，支持任意转换比例，并在多次调用之间保持滤波状态
+
+use crate::core::sample::{from_float_buffer, to_float_buffer, Sample};
+
+const DEFAULT_ZERO_CROSSINGS: usize = 16;  // sinc核每侧保留的过零点数
+const DEFAULT_PHASES: usize = 256;          // 多相滤波器组的相位数
+const KAISER_BETA: f64 = 8.6;              // Kaiser窗形状参数（约 -90dB 旁瓣）
+const ROLLOFF: f64 = 0.94;                 // 截止频率相对于奈奎斯特频率的比例
+
+/// 流式重采样器
+///
+/// 输入输出均为交错排列的多声道浮点采样。每次调用 `process` 时，
+/// 不足以计算下一个输出点的尾部输入会被保留到下一次调用，
+/// 因此可以直接用于任意大小的设备回调缓冲区。
+pub struct Resampler {
+    input_rate: u32,
+    output_rate: u32,
+    channels: usize,
+    step: f64,          // 每个输出采样对应的输入采样数
+    half_taps: usize,   // 滤波器单侧抽头数
+    phases: usize,
+    filter: Vec<f32>,   // (phases + 1) 组滤波器系数，每组 2 * half_taps 个
+    history: Vec<f32>,  // 尚未完全消耗的输入（交错排列）
+    position: f64,      // 下一个输出采样在history中的位置（单位：帧）
+}
+
+impl Resampler {
+    /// 创建新的重采样器
+    pub fn new(input_rate: u32, output_rate: u32, channels: u16) -> Self {
+        Self::with_quality(input_rate, output_rate, channels, DEFAULT_ZERO_CROSSINGS)
+    }
+
+    /// 使用指定的sinc过零点数创建重采样器（数值越大质量越高、延迟越大）
+    pub fn with_quality(input_rate: u32, output_rate: u32, channels: u16, zero_crossings: usize) -> Self {
+        assert!(input_rate > 0 && output_rate > 0, "sample rates must be positive");
+        assert!(channels > 0, "channel count must be positive");
+
+        // 降采样时需要把截止频率降低到输出端的奈奎斯特频率以下
+        let scale = (output_rate as f64 / input_rate as f64).min(1.0);
+        let cutoff = ROLLOFF * scale;
+        let half_taps = ((zero_crossings.max(1) as f64) / cutoff).ceil() as usize;
+
+        let mut resampler = Resampler {
+            input_rate,
+            output_rate,
+            channels: channels as usize,
+            step: input_rate as f64 / output_rate as f64,
+            half_taps,
+            phases: DEFAULT_PHASES,
+            filter: build_filter_bank(half_taps, DEFAULT_PHASES, cutoff),
+            history: Vec::new(),
+            position: 0.0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// 输入采样率
+    pub fn input_rate(&self) -> u32 {
+        self.input_rate
+    }
+
+    /// 输出采样率
+    pub fn output_rate(&self) -> u32 {
+        self.output_rate
+    }
+
+    /// 声道数
+    pub fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    /// 是否为直通（输入输出采样率相同）
+    pub fn is_passthrough(&self) -> bool {
+        self.input_rate == self.output_rate
+    }
+
+    /// 滤波器引入的延迟（以输出采样帧计）
+    pub fn latency_frames(&self) -> usize {
+        if self.is_passthrough() {
+            return 0;
+        }
+        (self.half_taps as f64 / self.step).ceil() as usize
+    }
+
+    /// 处理一段交错排列的输入，返回当前可以计算出的全部输出
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::with_capacity(
+            ((input.len() as f64 / self.step).ceil() as usize + self.channels) * 2,
+        );
+        self.process_into(input, &mut output);
+        output
+    }
+
+    /// 处理输入并将结果追加到 `output`，避免重复分配
+    pub fn process_into(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let channels = self.channels;
+        let taps = self.half_taps * 2;
+        self.history.extend_from_slice(input);
+        let frames = self.history.len() / channels;
+
+        loop {
+            let index = self.position.floor() as usize;
+            // 计算该输出点需要 index 之后 half_taps 帧的输入
+            if index + self.half_taps >= frames {
+                break;
+            }
+
+            let phase_pos = (self.position - index as f64) * self.phases as f64;
+            let phase = (phase_pos.floor() as usize).min(self.phases - 1);
+            let blend = (phase_pos - phase as f64) as f32;
+            let c0 = &self.filter[phase * taps..(phase + 1) * taps];
+            let c1 = &self.filter[(phase + 1) * taps..(phase + 2) * taps];
+            let start = index + 1 - self.half_taps;
+
+            for channel in 0..channels {
+                let mut acc = 0.0f32;
+                for k in 0..taps {
+                    let coeff = c0[k] + (c1[k] - c0[k]) * blend;
+                    acc += coeff * self.history[(start + k) * channels + channel];
+                }
+                output.push(acc);
+            }
+
+            self.position += self.step;
+        }
+
+        // 丢弃之后不再需要的输入帧
+        let consumed = (self.position.floor() as usize + 1)
+            .saturating_sub(self.half_taps)
+            .min(frames);
+        self.history.drain(..consumed * channels);
+        self.position -= consumed as f64;
+    }
+
+    /// 处理任意类型的采样（内部转换为浮点处理）
+    pub fn process_samples<S: Sample>(&mut self, input: &[S]) -> Vec<S> {
+        from_float_buffer(&self.process(&to_float_buffer(input)))
+    }
+
+    /// 冲刷滤波器中剩余的输入，返回尾部输出
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.is_passthrough() {
+            return Vec::new();
+        }
+        let padding = vec![0.0; self.half_taps * self.channels];
+        let output = self.process(&padding);
+        self.reset();
+        output
+    }
+
+    /// 重置内部状态
+    pub fn reset(&mut self) {
+        // 预先填充 half_taps - 1 帧静音，使第一个输出与第一个输入对齐
+        self.history.clear();
+        self.history.resize((self.half_taps - 1) * self.channels, 0.0);
+        self.position = (self.half_taps - 1) as f64;
+    }
+}
+
+/// 构建多相滤波器组，相邻两组之间在运行时做线性插值
+fn build_filter_bank(half_taps: usize, phases: usize, cutoff: f64) -> Vec<f32> {
+    let taps = half_taps * 2;
+    let mut filter = Vec::with_capacity((phases + 1) * taps);
+    let norm = bessel_i0(KAISER_BETA);
+
+    for phase in 0..=phases {
+        let frac = phase as f64 / phases as f64;
+        let coeffs: Vec<f64> = (0..taps)
+            .map(|k| {
+                let x = k as f64 - (half_taps as f64 - 1.0) - frac;
+                let ratio = x / half_taps as f64;
+                if ratio.abs() > 1.0 {
+                    return 0.0;
+                }
+                let window = bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).sqrt()) / norm;
+                cutoff * sinc(cutoff * x) * window
+            })
+            .collect();
+
+        // 每个相位归一化为单位直流增益
+        let sum: f64 = coeffs.iter().sum();
+        filter.extend(coeffs.iter().map(|&c| (c / sum) as f32));
+    }
+
+    filter
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// 第一类零阶修正贝塞尔函数（级数展开）
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..50 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32, rate: u32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_resampler_chunked_matches_one_shot() {
+        let input = tone(440.0, 44100, 44100);
+
+        let mut one_shot = Resampler::new(44100, 16000, 1);
+        let expected = one_shot.process(&input);
+
+        // 模拟设备回调的不规则缓冲区大小
+        let mut chunked = Resampler::new(44100, 16000, 1);
+        let mut actual = Vec::new();
+        let mut offset = 0;
+        for size in [1usize, 7, 512, 441, 1024, 3].iter().cycle() {
+            if offset >= input.len() {
+                break;
+            }
+            let end = (offset + size).min(input.len());
+            chunked.process_into(&input[offset..end], &mut actual);
+            offset = end;
+        }
+
+        assert_eq!(expected.len(), actual.len());
+        for (a, b) in expected.iter().zip(actual.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_resampler_frequency_response() {
+        // 48kHz -> 16kHz：1kHz应无损通过，10kHz应被抗混叠滤波器滤除
+        let mut resampler = Resampler::new(48000, 16000, 1);
+        let mut passband = resampler.process(&tone(1000.0, 48000, 48000));
+        passband.extend(resampler.flush());
+        assert!((passband.len() as i64 - 16000).abs() <= 1);
+        let level = rms(&passband[1000..15000]);
+        assert!((level - 0.5 / 2f32.sqrt()).abs() < 0.01, "passband rms {}", level);
+
+        let mut resampler = Resampler::new(48000, 16000, 1);
+        let stopband = resampler.process(&tone(10000.0, 48000, 48000));
+        assert!(rms(&stopband[1000..15000]) < 0.005);
+    }
+
+    #[test]
+    fn test_resampler_upsample_stereo() {
+        // 16kHz -> 48kHz 双声道，左右声道互不干扰
+        let left = tone(500.0, 16000, 1600);
+        let interleaved: Vec<f32> = left.iter().flat_map(|&l| [l, 0.0]).collect();
+
+        let mut resampler = Resampler::new(16000, 48000, 2);
+        let output = resampler.process(&interleaved);
+        assert_eq!(output.len() % 2, 0);
+
+        let (l, r): (Vec<f32>, Vec<f32>) = output.chunks_exact(2).map(|f| (f[0], f[1])).unzip();
+        assert!(rms(&l[300..]) > 0.3);
+        assert!(r.iter().all(|s| s.abs() < 1e-6));
+    }
+}
@@ -0,0 +1,224 @@
+//! 时间伸缩模块
+//! WSOLA（波形相似叠加）变速不变调，把译文语音压缩到说话人的时间窗口内
+
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::audio_types::AudioSample;
+use crate::core::sample::{Ditherer, Sample};
+use crate::dsp::fft::hann_window;
+
+const FRAME_MS: f32 = 20.0;      // 叠加帧长度，覆盖至少两个基音周期
+const TOLERANCE_MS: f32 = 5.0;   // 分析帧位置的搜索范围（±）
+
+/// WSOLA时间伸缩器
+///
+/// 合成帧按半帧步长叠加，分析帧在名义位置附近搜索与上一帧自然延续最相似的波形，
+/// 避免相位不连续，因此音高和音色保持不变。
+pub struct TimeStretcher {
+    frame: usize,
+    tolerance: usize,
+    window: Vec<f32>,
+}
+
+impl TimeStretcher {
+    /// 创建时间伸缩器
+    pub fn new(sample_rate: u32) -> Self {
+        let frame = ((sample_rate as f32 * FRAME_MS / 1000.0) as usize / 2 * 2).max(4);
+        TimeStretcher {
+            frame,
+            tolerance: (sample_rate as f32 * TOLERANCE_MS / 1000.0) as usize,
+            window: hann_window(frame),
+        }
+    }
+
+    /// 按速率伸缩（`rate` > 1 加快、< 1 放慢），输出长度约为输入长度 / `rate`
+    pub fn stretch(&self, input: &[f32], rate: f32) -> Vec<f32> {
+        let rate = rate.clamp(0.25, 4.0);
+        let length = (input.len() as f64 / rate as f64).round() as usize;
+        self.stretch_to_length(input, length)
+    }
+
+    /// 伸缩到指定长度（采样数）
+    pub fn stretch_to_length(&self, input: &[f32], length: usize) -> Vec<f32> {
+        if input.len() < self.frame * 2 || length == input.len() || length == 0 {
+            return resize_edge(input, length);
+        }
+
+        let frame = self.frame;
+        let hop = frame / 2;
+        let rate = input.len() as f64 / length as f64;
+
+        // 末尾补零，最后几帧的搜索和叠加不越界
+        let mut padded = input.to_vec();
+        padded.resize(input.len() + frame + self.tolerance * 2, 0.0);
+        let last_start = padded.len() - frame;
+
+        let mut output = vec![0.0f32; length + frame];
+        let mut norm = vec![0.0f32; length + frame];
+        let mut previous = 0usize;
+        let mut out_pos = 0usize;
+        let mut k = 0usize;
+
+        while out_pos < length {
+            let nominal = ((k as f64 * hop as f64 * rate).round() as usize).min(last_start);
+            let position = if k == 0 {
+                0
+            } else {
+                self.best_match(&padded, previous + hop, nominal, last_start)
+            };
+
+            for i in 0..frame {
+                output[out_pos + i] += padded[position + i] * self.window[i];
+                norm[out_pos + i] += self.window[i];
+            }
+
+            previous = position;
+            out_pos += hop;
+            k += 1;
+        }
+
+        output.truncate(length);
+        for (sample, &weight) in output.iter_mut().zip(norm.iter()) {
+            if weight > 1e-3 {
+                *sample /= weight;
+            }
+        }
+        output
+    }
+
+    /// 按速率伸缩流水线采样（带抖动量化）
+    pub fn stretch_audio(&self, input: &[AudioSample], rate: f32) -> Vec<AudioSample> {
+        let length = (input.len() as f64 / rate.clamp(0.25, 4.0) as f64).round() as usize;
+        self.stretch_audio_to_length(input, length)
+    }
+
+    /// 把流水线采样伸缩到指定长度（带抖动量化）
+    pub fn stretch_audio_to_length(&self, input: &[AudioSample], length: usize) -> Vec<AudioSample> {
+        let samples: Vec<f32> = input.iter().map(|s| s.to_f32()).collect();
+        let stretched = self.stretch_to_length(&samples, length);
+        let mut ditherer = Ditherer::new();
+        stretched.iter().map(|&s| ditherer.quantize(s)).collect()
+    }
+
+    /// 在 [nominal - tolerance, nominal + tolerance] 内寻找与 `natural` 处波形最相似的分析帧
+    fn best_match(&self, input: &[f32], natural: usize, nominal: usize, last_start: usize) -> usize {
+        let natural = natural.min(last_start);
+        let reference = &input[natural..natural + self.frame];
+        let start = nominal.saturating_sub(self.tolerance);
+        let end = (nominal + self.tolerance).min(last_start);
+
+        let mut best = nominal;
+        let mut best_score = f32::NEG_INFINITY;
+        for candidate in start..=end {
+            let segment = &input[candidate..candidate + self.frame];
+            let mut correlation = 0.0;
+            let mut energy = 0.0;
+            for (a, b) in reference.iter().zip(segment.iter()) {
+                correlation += a * b;
+                energy += b * b;
+            }
+            let score = correlation / (energy.sqrt() + 1e-6);
+            if score > best_score {
+                best_score = score;
+                best = candidate;
+            }
+        }
+        best
+    }
+}
+
+/// 过短的片段无法做叠加，直接截断或补零
+fn resize_edge(input: &[f32], length: usize) -> Vec<f32> {
+    let mut output = input[..length.min(input.len())].to_vec();
+    output.resize(length, 0.0);
+    output
+}
+
+/// 追赶策略：根据播放队列积压决定译文语音的加速倍率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatchUpPolicy {
+    pub target_backlog_ms: u64,  // 可接受的播放积压，超出部分在下一段语音中追回
+    pub max_rate: f32,           // 最大加速倍率，超过后可懂度明显下降
+}
+
+impl Default for CatchUpPolicy {
+    fn default() -> Self {
+        CatchUpPolicy {
+            target_backlog_ms: 500,
+            max_rate: 1.5,
+        }
+    }
+}
+
+impl CatchUpPolicy {
+    /// 计算时长为 `clip` 的语音在当前积压 `backlog` 下的播放倍率（≥ 1.0）
+    ///
+    /// 目标是在这段语音播放期间消化掉超出 `target_backlog_ms` 的积压：
+    /// clip - clip / rate = backlog - target。
+    pub fn rate_for(&self, backlog: Duration, clip: Duration) -> f32 {
+        let excess = backlog.saturating_sub(Duration::from_millis(self.target_backlog_ms));
+        if excess.is_zero() || clip.is_zero() {
+            return 1.0;
+        }
+        if excess >= clip {
+            return self.max_rate.max(1.0);
+        }
+        let clip = clip.as_secs_f32();
+        (clip / (clip - excess.as_secs_f32())).clamp(1.0, self.max_rate.max(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    /// 用过零次数估计基频
+    fn pitch(samples: &[f32]) -> f32 {
+        let crossings = samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        crossings as f32 * RATE as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn test_stretch_preserves_pitch() {
+        // 带谐波的200Hz“元音”
+        let voice: Vec<f32> = (0..RATE as usize * 2)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let w = 2.0 * std::f32::consts::PI * 200.0 * t;
+                0.5 * w.sin() + 0.2 * (2.0 * w).sin() + 0.1 * (3.0 * w).sin()
+            })
+            .collect();
+        let stretcher = TimeStretcher::new(RATE);
+
+        for rate in [1.5f32, 1.25, 0.8] {
+            let output = stretcher.stretch(&voice, rate);
+            let expected = (voice.len() as f32 / rate).round() as usize;
+            assert_eq!(output.len(), expected);
+            // 变速不变调，电平不变
+            let f0 = pitch(&output[1600..output.len() - 1600]);
+            assert!((f0 - 200.0).abs() < 4.0, "rate {} -> {} Hz", rate, f0);
+            let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+            assert!((rms(&output[1600..output.len() - 1600]) - rms(&voice)).abs() < 0.03);
+        }
+
+        assert_eq!(stretcher.stretch_to_length(&voice, 20000).len(), 20000);
+        assert_eq!(stretcher.stretch_audio(&[1000i16; 8000], 2.0).len(), 4000);
+    }
+
+    #[test]
+    fn test_catch_up_policy() {
+        let policy = CatchUpPolicy::default();
+        let clip = Duration::from_secs(2);
+
+        // 积压在目标范围内不加速
+        assert_eq!(policy.rate_for(Duration::from_millis(300), clip), 1.0);
+        // 超出0.5秒：2秒语音压到1.5秒播完
+        let rate = policy.rate_for(Duration::from_millis(1000), clip);
+        assert!((rate - 2.0 / 1.5).abs() < 1e-3);
+        // 积压过大时限制在最大倍率
+        assert_eq!(policy.rate_for(Duration::from_secs(5), clip), 1.5);
+    }
+}
@@ -3,7 +3,7 @@
 
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use crate::{AudioSample, SAMPLE_RATE};
+use crate::{AudioBuffer, AudioFormat, AudioSample, FormatMismatch, SAMPLE_RATE};
 
 /// 识别结果结构
 #[derive(Debug, Clone)]
@@ -79,6 +79,11 @@ impl Asr {
 
     /// 处理音频数据（流式）
     pub fn process_audio(&mut self, audio_data: &[AudioSample]) -> Vec<AsrResult> {
+        self.process_segment(audio_data, true)
+    }
+
+    /// 识别一个语音段；`is_final` 为 false 表示语音段是强制切分出的前半部分，这句话尚未结束
+    pub fn process_segment(&mut self, audio_data: &[AudioSample], is_final: bool) -> Vec<AsrResult> {
         let mut results = Vec::new();
         
         if !self.initialized || !self.model_loaded {
@@ -92,7 +97,7 @@ impl Asr {
             text: "模拟识别结果".to_string(), // 实际应用中这里会是真实的识别文本
             confidence: 0.9,
             is_partial: false,
-            is_final: true,
+            is_final,
             timestamp: Instant::now(),
             word_timings: vec![],
         };
@@ -107,6 +112,17 @@ impl Asr {
         results
     }
 
+    /// ASR模型期望的输入格式
+    pub fn input_format(&self) -> AudioFormat {
+        AudioFormat::PIPELINE
+    }
+
+    /// 处理带格式信息的音频缓冲区，格式与模型不一致时返回错误
+    pub fn process_buffer(&mut self, buffer: &AudioBuffer) -> Result<Vec<AsrResult>, FormatMismatch> {
+        self.input_format().check_compatible(&buffer.format)?;
+        Ok(self.process_audio(&buffer.samples))
+    }
+
     /// 处理音频帧（实时）
     pub fn process_frame(&mut self, audio_frame: &[AudioSample]) -> AsrResult {
         if !self.initialized || !self.model_loaded {
@@ -0,0 +1,288 @@
+//! 高斯混合模型VAD模块
+//! 仿 WebRTC VAD：6个子带对数能量与过零率作为特征，语音/噪声各一组高斯混合模型求似然比，模型随输入自适应
+
+use crate::audio_types::AudioSample;
+use crate::core::sample::Sample;
+use crate::dsp::fft::{hann_window, Complex, Fft};
+use crate::engine::vad::VadEngine;
+
+const BANDS_HZ: [(f32, f32); 6] = [
+    (80.0, 250.0), (250.0, 500.0), (500.0, 1000.0),
+    (1000.0, 2000.0), (2000.0, 3000.0), (3000.0, 4000.0),
+];
+const INIT_FRAMES: usize = 10;        // 启动阶段用输入直接初始化噪声模型
+const NOISE_RATE: f32 = 0.05;         // 噪声模型自适应速率
+const SPEECH_RATE: f32 = 0.02;        // 语音模型自适应速率
+const MIN_GAP_DB: f32 = 6.0;          // 语音模型均值至少高出噪声模型的量
+const FLOOR_RISE_DB_PER_S: f32 = 3.0; // 子带能量下限允许上升的速度
+const ZCR_WEIGHT: f32 = 0.5;          // 过零率似然比的权重
+const MIN_STD_DB: f32 = 2.0;
+const MAX_STD_DB: f32 = 12.0;
+
+/// 一维高斯分布
+#[derive(Debug, Clone, Copy)]
+struct Gaussian {
+    mean: f32,
+    std: f32,
+}
+
+impl Gaussian {
+    fn log_pdf(&self, x: f32) -> f32 {
+        let z = (x - self.mean) / self.std;
+        -0.5 * z * z - self.std.ln() - 0.918_938_5  // ln(sqrt(2π))
+    }
+
+    /// 向观测值移动，`rate` 已乘上该分量的后验权重
+    fn adapt(&mut self, x: f32, rate: f32, min_std: f32, max_std: f32) {
+        let delta = x - self.mean;
+        self.mean += rate * delta;
+        let variance = self.std * self.std + rate * (delta * delta - self.std * self.std);
+        self.std = variance.max(0.0).sqrt().clamp(min_std, max_std);
+    }
+}
+
+/// 两分量等权高斯混合
+#[derive(Debug, Clone, Copy)]
+struct Mixture {
+    components: [Gaussian; 2],
+}
+
+impl Mixture {
+    fn new(means: [f32; 2], std: f32) -> Self {
+        Mixture { components: means.map(|mean| Gaussian { mean, std }) }
+    }
+
+    fn log_likelihood(&self, x: f32) -> f32 {
+        let [a, b] = self.components.map(|c| c.log_pdf(x));
+        let max = a.max(b);
+        max + ((a - max).exp() + (b - max).exp()).ln() - std::f32::consts::LN_2
+    }
+
+    /// 按各分量的后验权重自适应
+    fn adapt(&mut self, x: f32, rate: f32) {
+        let [a, b] = self.components.map(|c| c.log_pdf(x));
+        let weight_a = 1.0 / (1.0 + (b - a).exp());
+        self.components[0].adapt(x, rate * weight_a, MIN_STD_DB, MAX_STD_DB);
+        self.components[1].adapt(x, rate * (1.0 - weight_a), MIN_STD_DB, MAX_STD_DB);
+    }
+
+    fn max_mean(&self) -> f32 {
+        self.components[0].mean.max(self.components[1].mean)
+    }
+}
+
+/// WebRTC风格的GMM语音检测引擎
+///
+/// 噪声模型在非语音帧上自适应，并受子带能量下限（最小值跟踪）约束，
+/// 因此在噪声电平变化后仍能区分语音；语音模型均值始终保持在噪声模型之上。
+pub struct GmmVad {
+    fft: Fft,
+    bins: Vec<(usize, usize)>,
+    noise: Vec<Mixture>,
+    speech: Vec<Mixture>,
+    noise_zcr: Gaussian,
+    speech_zcr: Gaussian,
+    floor: Vec<f32>,
+    frames: usize,
+    sample_rate: u32,
+    spectrum: Vec<Complex>,
+}
+
+impl GmmVad {
+    /// 创建GMM检测引擎
+    pub fn new(sample_rate: u32) -> Self {
+        let mut vad = GmmVad {
+            fft: Fft::new(64),
+            bins: Vec::new(),
+            noise: Vec::new(),
+            speech: Vec::new(),
+            noise_zcr: Gaussian { mean: 0.3, std: 0.15 },
+            speech_zcr: Gaussian { mean: 0.1, std: 0.06 },
+            floor: Vec::new(),
+            frames: 0,
+            sample_rate,
+            spectrum: Vec::new(),
+        };
+        vad.reset_models();
+        vad
+    }
+
+    /// 提取特征：6个子带的均方能量（dBFS）与过零率
+    fn features(&mut self, frame: &[f32]) -> ([f32; 6], f32) {
+        let size = frame.len().next_power_of_two().max(64);
+        // 子带表在首帧建立，之后只在FFT长度变化时重建
+        if size != self.fft.size() || self.bins.is_empty() {
+            let bin_hz = self.sample_rate as f32 / size as f32;
+            self.fft = Fft::new(size);
+            self.bins = BANDS_HZ.iter()
+                .map(|&(low, high)| {
+                    let start = (low / bin_hz).round() as usize;
+                    let end = ((high / bin_hz).round() as usize).clamp(start + 1, size / 2 + 1);
+                    (start.min(size / 2), end)
+                })
+                .collect();
+        }
+
+        let window = hann_window(frame.len());
+        let window_energy: f32 = window.iter().map(|w| w * w).sum::<f32>().max(1e-6);
+        let mut windowed: Vec<f32> = frame.iter().zip(window.iter()).map(|(x, w)| x * w).collect();
+        windowed.resize(size, 0.0);
+        self.fft.forward_real(&windowed, &mut self.spectrum);
+
+        let scale = 2.0 / (size as f32 * window_energy);
+        let mut bands = [0.0f32; 6];
+        for (band, &(start, end)) in bands.iter_mut().zip(self.bins.iter()) {
+            let power: f32 = self.spectrum[start..end].iter().map(|c| c.norm_sqr()).sum::<f32>() * scale;
+            *band = 10.0 * (power + 1e-12).log10();
+        }
+
+        let crossings = frame.windows(2).filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0)).count();
+        (bands, crossings as f32 / frame.len().max(1) as f32)
+    }
+
+    fn reset_models(&mut self) {
+        self.noise = vec![Mixture::new([-70.0, -60.0], 6.0); BANDS_HZ.len()];
+        self.speech = vec![Mixture::new([-45.0, -30.0], 8.0); BANDS_HZ.len()];
+        self.noise_zcr = Gaussian { mean: 0.3, std: 0.15 };
+        self.speech_zcr = Gaussian { mean: 0.1, std: 0.06 };
+        self.floor = vec![-120.0; BANDS_HZ.len()];
+        self.frames = 0;
+    }
+}
+
+impl VadEngine for GmmVad {
+    fn name(&self) -> &str {
+        "gmm"
+    }
+
+    fn speech_probability(&mut self, frame: &[AudioSample]) -> f32 {
+        if frame.len() < 2 {
+            return 0.0;
+        }
+        let samples: Vec<f32> = frame.iter().map(|s| s.to_f32()).collect();
+        let (bands, zcr) = self.features(&samples);
+        let floor_rise = FLOOR_RISE_DB_PER_S * frame.len() as f32 / self.sample_rate as f32;
+        self.frames += 1;
+
+        // 启动阶段假定为背景噪声，直接初始化噪声模型
+        if self.frames <= INIT_FRAMES {
+            let n = self.frames as f32;
+            for (b, &x) in bands.iter().enumerate() {
+                let previous = if self.frames == 1 { x } else { self.floor[b] };
+                let mean = previous + (x - previous) / n;
+                self.floor[b] = mean;
+                self.noise[b] = Mixture::new([mean - 3.0, mean + 3.0], 4.0);
+                for component in self.speech[b].components.iter_mut() {
+                    component.mean = component.mean.max(mean + 3.0 + MIN_GAP_DB);
+                }
+            }
+            self.noise_zcr.adapt(zcr, 1.0 / n, 0.02, 0.3);
+            return 0.0;
+        }
+
+        let band_llr: f32 = bands.iter()
+            .enumerate()
+            .map(|(b, &x)| self.speech[b].log_likelihood(x) - self.noise[b].log_likelihood(x))
+            .sum::<f32>() / BANDS_HZ.len() as f32;
+        let zcr_llr = self.speech_zcr.log_pdf(zcr) - self.noise_zcr.log_pdf(zcr);
+        let llr = (band_llr + ZCR_WEIGHT * zcr_llr).clamp(-30.0, 30.0);
+        let probability = 1.0 / (1.0 + (-llr).exp());
+
+        // 模型自适应
+        for (b, &x) in bands.iter().enumerate() {
+            self.floor[b] = (self.floor[b] + floor_rise).min(x);
+            if probability < 0.5 {
+                self.noise[b].adapt(x, NOISE_RATE * (1.0 - probability));
+            } else {
+                self.speech[b].adapt(x, SPEECH_RATE * probability);
+            }
+            for component in self.noise[b].components.iter_mut() {
+                component.mean = component.mean.max(self.floor[b]);
+            }
+            let min_speech = self.noise[b].max_mean() + MIN_GAP_DB;
+            for component in self.speech[b].components.iter_mut() {
+                component.mean = component.mean.max(min_speech);
+            }
+        }
+        if probability < 0.5 {
+            self.noise_zcr.adapt(zcr, NOISE_RATE * (1.0 - probability), 0.02, 0.3);
+        } else {
+            self.speech_zcr.adapt(zcr, SPEECH_RATE * probability, 0.02, 0.3);
+        }
+
+        probability
+    }
+
+    fn reset(&mut self) {
+        self.reset_models();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rng::tests::white_noise;
+    use crate::core::rng::XorShift32;
+    use crate::core::sample::Ditherer;
+
+    const RATE: u32 = 16000;
+
+    #[test]
+    fn test_gmm_separates_voice_from_loud_noise() {
+        let mut rng = XorShift32::new(99);
+        let mut noise = move || rng.next_signed() * 0.0055;  // 约 -50 dBFS 白噪声
+        let mut ditherer = Ditherer::new();
+        let mut frame = |voiced: bool, start: usize| -> Vec<AudioSample> {
+            (start..start + 480)
+                .map(|i| {
+                    let t = i as f32 / RATE as f32;
+                    let w = 2.0 * std::f32::consts::PI * 150.0 * t;
+                    let voice = if voiced { 0.1 * (w.sin() + 0.5 * (2.0 * w).sin() + 0.3 * (3.0 * w).sin()) } else { 0.0 };
+                    ditherer.quantize(voice + noise())
+                })
+                .collect()
+        };
+
+        let mut vad = GmmVad::new(RATE);
+        let mut probabilities = Vec::new();
+        let pattern = [(false, 40), (true, 20), (false, 20)];
+        let mut position = 0;
+        for &(voiced, count) in &pattern {
+            for _ in 0..count {
+                probabilities.push(vad.speech_probability(&frame(voiced, position)));
+                position += 480;
+            }
+        }
+
+        // 噪声模型适应后，-50 dBFS 的宽带噪声不判为语音，谐波语音判为语音
+        assert!(probabilities[15..40].iter().all(|&p| p < 0.2), "{:?}", &probabilities[15..40]);
+        assert!(probabilities[42..60].iter().all(|&p| p > 0.8), "{:?}", &probabilities[40..60]);
+        assert!(probabilities[65..].iter().all(|&p| p < 0.2), "{:?}", &probabilities[60..]);
+
+        vad.reset();
+        assert_eq!(vad.speech_probability(&frame(true, 0)), 0.0);
+    }
+
+    #[test]
+    fn test_short_frames_measure_band_energy() {
+        // 4ms帧（64个采样）与初始FFT等长，子带表同样要建立，否则各子带能量都是0dBFS
+        let mut vad = GmmVad::new(RATE);
+        let (bands, _) = vad.features(&white_noise(64, 0.0055, 3));
+        assert!(bands.iter().all(|&b| b < -40.0), "{:?}", bands);
+
+        let mut vad = GmmVad::new(RATE);
+        let voice = |start: usize| -> Vec<AudioSample> {
+            (start..start + 64)
+                .map(|i| {
+                    let w = 2.0 * std::f32::consts::PI * 150.0 * i as f32 / RATE as f32;
+                    AudioSample::from_f32(0.1 * (w.sin() + 0.5 * (2.0 * w).sin() + 0.3 * (3.0 * w).sin()))
+                })
+                .collect()
+        };
+        let noise: Vec<AudioSample> = white_noise(64 * 200, 0.0055, 5).into_iter().map(AudioSample::from_f32).collect();
+        let quiet: Vec<f32> = noise.chunks(64).map(|frame| vad.speech_probability(frame)).collect();
+        assert!(quiet[20..].iter().all(|&p| p < 0.5), "{:?}", &quiet[20..]);
+        let loud: Vec<f32> = (0..50).map(|n| vad.speech_probability(&voice(n * 64))).collect();
+        assert!(loud[5..].iter().all(|&p| p > 0.5), "{:?}", loud);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::path::Path;
 use anyhow::Result;
+use crate::engine::silero_vad::SileroVad;
 
 /// 模型类型枚举
 pub enum ModelType {
@@ -44,16 +45,15 @@ impl ModelLoader {
         Ok(())
     }
 
-    /// 加载Silero VAD模型 (占位符实现)
-    pub fn load_silero_vad_model<P: AsRef<Path>>(model_path: P) -> Result<()> {
+    /// 加载Silero VAD模型（官方 `silero_vad.onnx`，接口见 [`crate::engine::silero_vad`]）
+    pub fn load_silero_vad_model<P: AsRef<Path>>(model_path: P) -> Result<SileroVad> {
         let path = model_path.as_ref();
         if !Self::check_model_exists(path) {
             return Err(anyhow::anyhow!("Model file does not exist: {:?}", path));
         }
         
         println!("Loading Silero VAD model from: {:?}", path);
-        // 在实际实现中，这里会加载Silero VAD模型
-        Ok(())
+        SileroVad::load(path).map_err(|e| anyhow::anyhow!("{}", e))
     }
 }
 
@@ -0,0 +1,360 @@
+//! Silero VAD 模块
+//! 用 tract 在CPU上直接运行官方 `silero_vad.onnx`（v5，16kHz），跨窗口保持LSTM状态与上下文采样
+//!
+//! 模型接口：
+//!
+//! | 名称     | 方向 | 形状          | 说明                                   |
+//! |----------|------|---------------|----------------------------------------|
+//! | `input`  | 输入 | [1, 64 + 512] | 上一窗口末尾64个采样 + 本窗口512个采样 |
+//! | `state`  | 输入 | [2, 1, 128]   | LSTM状态，首个窗口为全零               |
+//! | `sr`     | 输入 | 标量 i64      | 采样率，固定为 16000                   |
+//! | `output` | 输出 | [1, 1]        | 本窗口的语音概率                       |
+//! | `stateN` | 输出 | [2, 1, 128]   | 送入下一窗口的LSTM状态                 |
+//!
+//! 流水线的VAD帧（30ms即480个采样）与模型窗口长度不同，输入先积累为512个采样的窗口再推理。
+//! 与参考实现（onnxruntime）逐窗口比对概率的方法见 `scripts/silero_vad_golden.py`。
+
+use std::path::Path;
+use tract_onnx::prelude::*;
+use crate::audio_types::AudioSample;
+use crate::core::sample::Sample;
+use crate::engine::vad::VadEngine;
+
+/// Silero VAD 加载结果类型
+pub type SileroResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// 16kHz模型每次推理的采样数
+pub const WINDOW_SAMPLES: usize = 512;
+const CONTEXT_SAMPLES: usize = 64;  // 每个窗口前拼接上一窗口末尾的采样
+const STATE_SHAPE: [usize; 3] = [2, 1, 128];
+const SAMPLE_RATE: i64 = 16000;
+
+/// Silero VAD v5 模型
+pub struct SileroVad {
+    model: TypedRunnableModel<TypedModel>,
+    state: Tensor,       // 跨窗口保持的LSTM状态
+    context: Vec<f32>,   // 上一窗口末尾的采样
+    pending: Vec<f32>,   // 不足一个窗口、等待后续输入的采样
+    probability: f32,    // 最近一个窗口的语音概率
+    windows: u64,
+    failure: Option<String>,  // 推理失败的原因，失效后直到重置都不再推理
+}
+
+impl SileroVad {
+    /// 从官方ONNX模型文件加载
+    pub fn load<P: AsRef<Path>>(path: P) -> SileroResult<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| format!("Silero VAD model {:?}: {}", path, e))?;
+        Self::from_bytes(&data)
+    }
+
+    /// 从ONNX模型文件内容加载
+    pub fn from_bytes(data: &[u8]) -> SileroResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_read(&mut &data[..])
+            .map_err(|e| format!("Not a Silero VAD ONNX model: {}", e))?;
+        Self::from_model(model)
+    }
+
+    /// 固定输入形状并优化模型，输入输出与v5接口不一致时加载失败
+    fn from_model(mut model: InferenceModel) -> SileroResult<Self> {
+        let inputs = model.input_outlets()?.to_vec();
+        let names: Vec<&str> = inputs.iter().map(|outlet| model.node(outlet.node).name.as_str()).collect();
+        if names != ["input", "state", "sr"] {
+            return Err(format!("Unsupported Silero VAD model inputs {:?}, expected v5 [input, state, sr]", names).into());
+        }
+        let outputs = model.output_outlets()?.len();
+        if outputs != 2 {
+            return Err(format!("Expected 2 Silero VAD model outputs, found {}", outputs).into());
+        }
+        model.set_input_fact(0, f32::fact([1, CONTEXT_SAMPLES + WINDOW_SAMPLES]).into())?;
+        model.set_input_fact(1, f32::fact(STATE_SHAPE).into())?;
+        model.set_input_fact(2, i64::scalar_fact().into())?;
+
+        let model = model
+            .into_optimized()
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| format!("Failed to prepare Silero VAD model: {}", e))?;
+        Ok(SileroVad {
+            model,
+            state: Tensor::zero::<f32>(&STATE_SHAPE)?,
+            context: vec![0.0; CONTEXT_SAMPLES],
+            pending: Vec::with_capacity(2 * WINDOW_SAMPLES),
+            probability: 0.0,
+            windows: 0,
+            failure: None,
+        })
+    }
+
+    /// 已处理的窗口数
+    pub fn windows_processed(&self) -> u64 {
+        self.windows
+    }
+
+    /// 推理一个512个采样的窗口，返回语音概率
+    ///
+    /// 窗口前拼接上一窗口末尾的64个采样作为上下文，LSTM状态在窗口之间保持。
+    pub fn process_window(&mut self, window: &[f32]) -> SileroResult<f32> {
+        if window.len() != WINDOW_SAMPLES {
+            return Err(format!("Expected {} samples per window, got {}", WINDOW_SAMPLES, window.len()).into());
+        }
+        let mut samples = Vec::with_capacity(CONTEXT_SAMPLES + WINDOW_SAMPLES);
+        samples.extend_from_slice(&self.context);
+        samples.extend_from_slice(window);
+        let input = Tensor::from_shape(&[1, CONTEXT_SAMPLES + WINDOW_SAMPLES], &samples)?;
+
+        let outputs = self.model.run(tvec!(
+            input.into(),
+            self.state.clone().into(),
+            tensor0(SAMPLE_RATE).into(),
+        ))?;
+        let probability = *outputs[0]
+            .as_slice::<f32>()?
+            .first()
+            .ok_or("Silero VAD model returned no probability")?;
+        self.state = outputs[1].clone().into_tensor();
+        self.context.copy_from_slice(&window[WINDOW_SAMPLES - CONTEXT_SAMPLES..]);
+        self.windows += 1;
+        Ok(probability)
+    }
+
+    /// 处理任意长度的流水线采样：凑满的窗口依次推理，返回最近一个窗口的语音概率
+    pub fn process_audio(&mut self, audio: &[AudioSample]) -> SileroResult<f32> {
+        self.pending.extend(audio.iter().map(|s| s.to_f32()));
+        let windows = self.pending.len() / WINDOW_SAMPLES;
+        for index in 0..windows {
+            let window = self.pending[index * WINDOW_SAMPLES..(index + 1) * WINDOW_SAMPLES].to_vec();
+            self.probability = self.process_window(&window)?;
+        }
+        self.pending.drain(..windows * WINDOW_SAMPLES);
+        Ok(self.probability)
+    }
+
+    /// 逐帧检测用：推理失败时只报告一次并标记模型失效，此后直到重置都按静音处理
+    pub fn frame_probability(&mut self, audio: &[AudioSample]) -> f32 {
+        if self.failure.is_some() {
+            return 0.0;
+        }
+        match self.process_audio(audio) {
+            Ok(probability) => probability,
+            Err(e) => {
+                eprintln!("Silero VAD inference failed, treating audio as silence until reset: {}", e);
+                self.failure = Some(e.to_string());
+                0.0
+            }
+        }
+    }
+
+    /// 推理失败的原因，模型正常时为 `None`
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    /// 清空循环状态、上下文、未凑满窗口的采样和失效标记（新的音频流开始时调用）
+    pub fn reset(&mut self) {
+        self.state = Tensor::zero::<f32>(&STATE_SHAPE).unwrap();
+        self.context.iter_mut().for_each(|v| *v = 0.0);
+        self.pending.clear();
+        self.probability = 0.0;
+        self.windows = 0;
+        self.failure = None;
+    }
+}
+
+impl VadEngine for SileroVad {
+    fn name(&self) -> &str {
+        "silero"
+    }
+
+    fn speech_probability(&mut self, frame: &[AudioSample]) -> f32 {
+        self.frame_probability(frame)
+    }
+
+    fn reset(&mut self) {
+        SileroVad::reset(self);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tract_onnx::pb;
+    use tract_onnx::pb::attribute_proto::AttributeType;
+    use tract_onnx::pb::tensor_proto::DataType;
+    use tract_onnx::pb::tensor_shape_proto::{dimension, Dimension};
+
+    fn value_info(name: &str, data_type: DataType, dims: &[i64]) -> pb::ValueInfoProto {
+        let dim = dims.iter().map(|&d| Dimension { value: Some(dimension::Value::DimValue(d)), ..Default::default() }).collect();
+        pb::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type: data_type as i32,
+                    shape: Some(pb::TensorShapeProto { dim }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn scalar(name: &str, value: f32) -> pb::TensorProto {
+        pb::TensorProto { name: name.to_string(), data_type: DataType::Float as i32, float_data: vec![value], ..Default::default() }
+    }
+
+    fn node(op_type: &str, inputs: &[&str], output: &str, axes: Option<(&[i64], i64)>) -> pb::NodeProto {
+        let attribute = axes.map(|(axes, keepdims)| vec![
+            pb::AttributeProto { name: "axes".to_string(), r#type: AttributeType::Ints as i32, ints: axes.to_vec(), ..Default::default() },
+            pb::AttributeProto { name: "keepdims".to_string(), r#type: AttributeType::Int as i32, i: keepdims, ..Default::default() },
+        ]).unwrap_or_default();
+        pb::NodeProto {
+            op_type: op_type.to_string(),
+            input: inputs.iter().map(|s| s.to_string()).collect(),
+            output: vec![output.to_string()],
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    /// If 的分支子图：引用外层的 `source` 作为输出
+    fn branch(name: &str, source: &str) -> pb::GraphProto {
+        pb::GraphProto {
+            name: name.to_string(),
+            node: vec![node("Identity", &[source], name, None)],
+            output: vec![value_info(name, DataType::Float, &[1, 1])],
+            ..Default::default()
+        }
+    }
+
+    /// 与官方模型接口一致的小模型：output = scale × Σinput + Σstate + bias，stateN = state + step
+    ///
+    /// 与官方模型一样按运行时的 `sr` 选择分支，`sr` 不是16000时输出为0。
+    fn interface_model(state_name: &str, scale: f32, step: f32, bias: f32) -> pb::ModelProto {
+        let mut select = node("If", &["is_16k"], "output", None);
+        select.attribute = vec![
+            pb::AttributeProto { name: "then_branch".to_string(), r#type: AttributeType::Graph as i32, g: Some(branch("then_out", "probability")), ..Default::default() },
+            pb::AttributeProto { name: "else_branch".to_string(), r#type: AttributeType::Graph as i32, g: Some(branch("else_out", "zero")), ..Default::default() },
+        ];
+        pb::ModelProto {
+            ir_version: 7,
+            opset_import: vec![pb::OperatorSetIdProto { domain: String::new(), version: 11 }],
+            graph: Some(pb::GraphProto {
+                name: "silero_vad_interface".to_string(),
+                node: vec![
+                    node("ReduceSum", &["input"], "input_sum", Some((&[1], 1))),
+                    node("Mul", &["input_sum", "scale"], "scaled", None),
+                    node("ReduceSum", &[state_name], "state_sum", Some((&[0, 1, 2], 0))),
+                    node("Add", &["scaled", "state_sum"], "logit", None),
+                    node("Add", &["logit", "bias"], "probability", None),
+                    node("Mul", &["probability", "scale_zero"], "zero", None),
+                    node("Equal", &["sr", "rate"], "is_16k", None),
+                    select,
+                    node("Add", &[state_name, "step"], "stateN", None),
+                ],
+                initializer: vec![
+                    scalar("scale", scale), scalar("bias", bias), scalar("step", step), scalar("scale_zero", 0.0),
+                    pb::TensorProto { name: "rate".to_string(), data_type: DataType::Int64 as i32, int64_data: vec![16000], ..Default::default() },
+                ],
+                input: vec![
+                    value_info("input", DataType::Float, &[1, 576]),
+                    value_info(state_name, DataType::Float, &[2, 1, 128]),
+                    value_info("sr", DataType::Int64, &[]),
+                ],
+                output: vec![
+                    value_info("output", DataType::Float, &[1, 1]),
+                    value_info("stateN", DataType::Float, &[2, 1, 128]),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// 与官方模型接口一致、输出恒为 `probability` 的模型
+    pub(crate) fn constant_model(probability: f32) -> SileroVad {
+        let proto = interface_model("state", 0.0, 0.0, probability);
+        SileroVad::from_model(tract_onnx::onnx().model_for_proto_model(&proto).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_windows_carry_context_and_state() {
+        let proto = interface_model("state", 1.0, 1.0, 0.0);
+        let mut vad = SileroVad::from_model(tract_onnx::onnx().model_for_proto_model(&proto).unwrap()).unwrap();
+
+        // 480个采样的VAD帧不足一个窗口，不推理
+        assert_eq!(vad.process_audio(&[16384; 480]).unwrap(), 0.0);
+        assert_eq!(vad.windows_processed(), 0);
+        // 凑满512个采样：上下文为零，Σinput = 512 × 0.5
+        assert_eq!(vad.process_audio(&[16384; 480]).unwrap(), 256.0);
+        // 第二个窗口带上一窗口末尾64个采样，状态经过一次 +1
+        assert_eq!(vad.process_audio(&[16384; 480]).unwrap(), 256.0 + 32.0 + 256.0);
+        assert_eq!(vad.windows_processed(), 2);
+
+        // 重置后与全新实例一致
+        vad.reset();
+        assert_eq!(vad.process_audio(&[16384; 960]).unwrap(), 256.0);
+        assert!(vad.process_window(&[0.0; 480]).is_err());
+    }
+
+    #[test]
+    fn test_inference_failure_marks_model_failed() {
+        // 输出状态的形状与输入不一致：第一个窗口正常，第二个窗口推理失败
+        let mut proto = interface_model("state", 0.0, 0.0, 0.5);
+        let graph = proto.graph.as_mut().unwrap();
+        let step = graph.initializer.iter_mut().find(|t| t.name == "step").unwrap();
+        step.dims = vec![2, 1, 1, 1];
+        step.float_data = vec![0.0; 2];
+        graph.output[1] = value_info("stateN", DataType::Float, &[2, 2, 1, 128]);
+        let mut vad = SileroVad::from_model(tract_onnx::onnx().model_for_proto_model(&proto).unwrap()).unwrap();
+
+        assert_eq!(vad.frame_probability(&[0; 512]), 0.5);
+        assert!(vad.failure().is_none());
+        assert_eq!(vad.frame_probability(&[0; 512]), 0.0);
+        assert!(vad.failure().is_some());
+        // 失效后不再推理
+        assert_eq!(vad.frame_probability(&[0; 512]), 0.0);
+        assert_eq!(vad.windows_processed(), 1);
+
+        vad.reset();
+        assert!(vad.failure().is_none());
+        assert_eq!(vad.frame_probability(&[0; 512]), 0.5);
+    }
+
+    #[test]
+    fn test_rejects_other_models() {
+        assert!(SileroVad::from_bytes(b"RIFF....").is_err());
+        assert!(SileroVad::load("missing_silero_vad.onnx").is_err());
+
+        // v4之前的模型以h/c输入LSTM状态
+        let proto = interface_model("h", 1.0, 1.0, 0.0);
+        let error = SileroVad::from_model(tract_onnx::onnx().model_for_proto_model(&proto).unwrap()).err().unwrap();
+        assert!(error.to_string().contains("Unsupported Silero VAD model inputs"));
+    }
+
+    /// 与参考实现逐窗口比对：`SILERO_VAD_MODEL` 为官方 `silero_vad.onnx`，
+    /// `SILERO_VAD_GOLDEN` 为 `scripts/silero_vad_golden.py` 在同一模型和固定音频上输出的JSON
+    #[test]
+    #[ignore = "requires the official silero_vad.onnx and a golden file from scripts/silero_vad_golden.py"]
+    fn test_matches_reference_model() {
+        let model = std::env::var("SILERO_VAD_MODEL").expect("SILERO_VAD_MODEL not set");
+        let golden = std::env::var("SILERO_VAD_GOLDEN").expect("SILERO_VAD_GOLDEN not set");
+        let golden: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(golden).unwrap()).unwrap();
+        let samples: Vec<AudioSample> = golden["samples"].as_array().unwrap()
+            .iter()
+            .map(|v| v.as_i64().unwrap() as AudioSample)
+            .collect();
+        let expected: Vec<f64> = golden["probabilities"].as_array().unwrap()
+            .iter()
+            .map(|v| v.as_f64().unwrap())
+            .collect();
+
+        let mut vad = SileroVad::load(model).unwrap();
+        let actual: Vec<f32> = samples.chunks_exact(WINDOW_SAMPLES)
+            .map(|window| vad.process_audio(window).unwrap())
+            .collect();
+        assert_eq!(actual.len(), expected.len());
+        for (index, (a, e)) in actual.iter().zip(&expected).enumerate() {
+            assert!((*a as f64 - e).abs() < 1e-4, "window {}: {} vs reference {}", index, a, e);
+        }
+    }
+}
@@ -9,8 +9,11 @@ use tokio::time::{interval, Duration};
 
 use crate::engine::asr::Asr;
 use crate::engine::mt::Mt;
-use crate::engine::vad::Vad;
-use crate::{AudioSample, SAMPLES_PER_FRAME};
+use crate::engine::vad::{AudioSegment, Vad, VadConfig};
+use crate::core::framer::Framer;
+use crate::core::tap_buffer::LagPolicy;
+use crate::core::ring_buffer::RingBuffer;
+use crate::{AudioFormat, AudioFrame, AudioSample, FormatMismatch, SAMPLES_PER_FRAME};
 
 /// 翻译结果结构
 #[derive(Debug, Clone)]
@@ -32,6 +35,9 @@ pub struct TranslationPipeline {
     asr: Arc<Mutex<Asr>>,
     mt: Arc<Mutex<Mt>>,
     vad: Arc<Mutex<Vad>>,
+    framer: Framer,                       // 将任意长度的输入整理为VAD帧
+    in_speech: bool,                      // VAD当前是否处于语音段内
+    segments: Arc<Mutex<Vec<(AudioSegment, bool)>>>, // VAD送出、等待识别的语音段及是否为最终段（含强制切分的部分）
     translation_callback: Option<TranslationCallback>,
     running: AtomicBool,
     source_language: Arc<Mutex<String>>,
@@ -40,16 +46,31 @@ pub struct TranslationPipeline {
 }
 
 impl TranslationPipeline {
-    /// 创建新的翻译流水线实例
+    /// 创建新的翻译流水线实例（能量VAD）
     pub fn new(asr_model_path: String, mt_model_path: String) -> Self {
+        Self::with_vad(asr_model_path, mt_model_path, Vad::new(crate::SAMPLE_RATE, 30))
+    }
+
+    /// 按VAD配置创建翻译流水线实例
+    pub fn with_vad_config(asr_model_path: String, mt_model_path: String, vad_config: &VadConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::with_vad(asr_model_path, mt_model_path, Vad::from_config(vad_config)?))
+    }
+
+    fn with_vad(asr_model_path: String, mt_model_path: String, mut vad: Vad) -> Self {
         let asr = Arc::new(Mutex::new(Asr::new(asr_model_path, "whisper-tiny".to_string())));
         let mt = Arc::new(Mutex::new(Mt::new(mt_model_path, "qwen2.5-0.5b".to_string())));
-        let vad = Arc::new(Mutex::new(Vad::new(crate::SAMPLE_RATE, 30)));
+        let framer = Framer::new(vad.format());
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        Self::queue_segments(&mut vad, &segments);
+        let vad = Arc::new(Mutex::new(vad));
 
         TranslationPipeline {
             asr,
             mt,
             vad,
+            framer,
+            in_speech: false,
+            segments,
             translation_callback: None,
             running: AtomicBool::new(false),
             source_language: Arc::new(Mutex::new("zh".to_string())),
@@ -58,6 +79,46 @@ impl TranslationPipeline {
         }
     }
 
+    /// 按配置替换VAD（分帧器随VAD帧长重建）
+    pub fn set_vad_config(&mut self, vad_config: &VadConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let mut vad = Vad::from_config(vad_config)?;
+        Self::queue_segments(&mut vad, &self.segments);
+        self.framer = Framer::new(vad.format());
+        self.in_speech = false;
+        self.segments.lock().unwrap().clear();
+        *self.vad.lock().unwrap() = vad;
+        Ok(())
+    }
+
+    /// VAD送出的语音段（无论是否为最终段）都放入队列，处理完当前帧后再送去识别
+    fn queue_segments(vad: &mut Vad, segments: &Arc<Mutex<Vec<(AudioSegment, bool)>>>) {
+        let segments = Arc::clone(segments);
+        vad.set_speech_segment_callback(Box::new(move |segment, is_final| {
+            segments.lock().unwrap().push((segment.clone(), is_final));
+        }));
+    }
+
+    /// 当前VAD检测引擎名称
+    pub fn vad_engine_name(&self) -> String {
+        self.vad.lock().unwrap().engine_name().to_string()
+    }
+
+    /// 订阅VAD事件流（语音开始/结束与语音段，按采样位置对齐），落后时按 `policy` 处理
+    pub fn vad_events(&self, policy: LagPolicy) -> crate::engine::vad::VadEventStream {
+        self.vad.lock().unwrap().events(policy)
+    }
+
+    /// 流水线期望的输入格式
+    pub fn input_format(&self) -> AudioFormat {
+        self.asr.lock().unwrap().input_format()
+    }
+
+    /// 在连接音频源之前检查其格式，不一致时在连接阶段报错
+    pub fn connect_input(&self, source_format: AudioFormat) -> Result<(), FormatMismatch> {
+        self.input_format().check_compatible(&source_format)?;
+        self.vad.lock().unwrap().format().check_compatible(&source_format)
+    }
+
     /// 设置翻译结果回调函数
     pub fn set_translation_callback(&mut self, callback: impl Fn(&TranslationResult) + Send + Sync + 'static) {
         self.translation_callback = Some(Arc::new(callback));
@@ -88,6 +149,11 @@ impl TranslationPipeline {
 
     /// 处理音频数据
     pub fn process_audio(&mut self, audio_data: &[AudioSample]) -> bool {
+        self.process_segment(audio_data, true)
+    }
+
+    /// 识别并翻译一个语音段，`is_final` 随识别结果带到翻译结果中
+    fn process_segment(&mut self, audio_data: &[AudioSample], is_final: bool) -> bool {
         if !self.running.load(Ordering::SeqCst) {
             return false;
         }
@@ -95,7 +161,7 @@ impl TranslationPipeline {
         // 使用ASR处理音频数据
         let asr_results = {
             let mut asr = self.asr.lock().unwrap();
-            asr.process_audio(audio_data)
+            asr.process_segment(audio_data, is_final)
         };
 
         // 处理ASR结果
@@ -124,6 +190,68 @@ impl TranslationPipeline {
         true
     }
 
+    /// 处理任意长度的音频流：先整理为定长帧，再逐帧经过VAD和ASR
+    ///
+    /// 返回本次输出的帧数
+    pub fn process_stream(&mut self, audio_data: &[AudioSample]) -> usize {
+        if !self.running.load(Ordering::SeqCst) {
+            return 0;
+        }
+
+        let frames = self.framer.push(audio_data);
+        for frame in &frames {
+            self.process_vad_frame(frame);
+        }
+        frames.len()
+    }
+
+    /// 处理上游已带采样时钟的音频帧：重新分为VAD帧并沿用其采样序号，再逐帧经过VAD和ASR
+    ///
+    /// 返回本次输出的帧数
+    pub fn process_audio_frame(&mut self, frame: &AudioFrame) -> usize {
+        if !self.running.load(Ordering::SeqCst) {
+            return 0;
+        }
+
+        let frames = self.framer.push_frame(frame);
+        for frame in &frames {
+            self.process_vad_frame(frame);
+        }
+        frames.len()
+    }
+
+    /// 一帧音频经过VAD，只有语音段内的帧才送去识别；VAD送出的完整语音段再整段识别并翻译
+    fn process_vad_frame(&mut self, frame: &AudioFrame) {
+        // 分帧器按VAD格式创建，格式必然一致
+        let result = match self.vad.lock().unwrap().process_audio_frame(frame) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+        if result.is_start_of_speech {
+            self.in_speech = true;
+        }
+        if result.is_end_of_speech {
+            self.in_speech = false;
+        }
+        if self.in_speech {
+            self.process_frame(&frame.samples);
+        }
+
+        let segments = std::mem::take(&mut *self.segments.lock().unwrap());
+        for (segment, is_final) in segments {
+            self.process_segment(&segment, is_final);
+        }
+    }
+
+    /// 异步等待缓冲区中至少一帧音频（或超时），然后按帧处理
+    ///
+    /// 返回本次输出的帧数
+    pub async fn process_from_buffer(&mut self, buffer: &RingBuffer<AudioSample>, timeout: Duration) -> usize {
+        let frame_len = self.framer.format().samples_per_frame();
+        let audio = buffer.read_at_least(frame_len, timeout).await;
+        self.process_stream(&audio)
+    }
+
     /// 启动翻译流水线
     pub fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if self.running.load(Ordering::SeqCst) {
@@ -146,6 +274,10 @@ impl TranslationPipeline {
         }
 
         self.running.store(true, Ordering::SeqCst);
+        self.framer.reset(Instant::now());
+        self.in_speech = false;
+        self.segments.lock().unwrap().clear();
+        self.vad.lock().unwrap().reset();
 
         // 重置ASR和MT模块
         {
@@ -215,7 +347,7 @@ impl TranslationPipeline {
 
     /// 内部ASR结果处理函数
     fn on_asr_result(&self, result: crate::engine::asr::AsrResult) {
-        if !result.is_final {
+        if result.is_partial {
             // 中间结果，可以发送给UI进行实时显示
             if let Some(ref callback) = self.translation_callback {
                 let trans_result = TranslationResult {
@@ -233,8 +365,9 @@ impl TranslationPipeline {
             return;
         }
 
-        // 最终ASR结果，提交给MT模块进行翻译
+        // 完整的ASR结果（强制切分出的语音段也算），提交给MT模块进行翻译
         let source_text = result.text.clone();
+        let is_final = result.is_final;
         let source_lang = self.source_language.lock().unwrap().clone();
         let target_lang = self.target_language.lock().unwrap().clone();
         
@@ -257,7 +390,7 @@ impl TranslationPipeline {
                     asr_confidence: 0.9, // 使用实际的ASR置信度
                     mt_confidence: mt_result.confidence,
                     is_partial: false,
-                    is_final: true,
+                    is_final,
                     timestamp: Instant::now(),
                 };
                 
@@ -299,4 +432,123 @@ mod tests {
         );
         assert!(!pipeline.is_running());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_stream_frames_input() {
+        let mut pipeline = TranslationPipeline::new(
+            "./models/whisper-tiny.bin".to_string(),
+            "./models/qwen2.5-0.5b.bin".to_string(),
+        );
+        pipeline.initialize().unwrap();
+        pipeline.start().unwrap();
+
+        // VAD帧为30ms（480个采样），不足一帧的部分留到下一次
+        assert_eq!(pipeline.process_stream(&vec![0; 1000]), 2);
+        assert_eq!(pipeline.process_stream(&vec![0; 500]), 1);
+        assert_eq!(pipeline.framer.next_sample_index(), 1440);
+    }
+
+    #[tokio::test]
+    async fn test_process_from_buffer_waits_for_audio() {
+        let mut pipeline = TranslationPipeline::new(
+            "./models/whisper-tiny.bin".to_string(),
+            "./models/qwen2.5-0.5b.bin".to_string(),
+        );
+        pipeline.initialize().unwrap();
+        pipeline.start().unwrap();
+
+        let buffer = Arc::new(RingBuffer::new(4096));
+        let producer = {
+            let buffer = Arc::clone(&buffer);
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                buffer.write(&[0; 960]);
+            })
+        };
+
+        assert_eq!(pipeline.process_from_buffer(&buffer, Duration::from_secs(5)).await, 2);
+        producer.await.unwrap();
+        assert_eq!(pipeline.process_from_buffer(&buffer, Duration::from_millis(5)).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_audio_frames_gated_by_vad_keep_sample_clock() {
+        use std::sync::atomic::AtomicUsize;
+        use tokio_stream::StreamExt;
+
+        let mut pipeline = TranslationPipeline::new(
+            "./models/whisper-tiny.bin".to_string(),
+            "./models/qwen2.5-0.5b.bin".to_string(),
+        );
+        let partials = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&partials);
+        pipeline.set_translation_callback(move |result| {
+            if result.is_partial {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        pipeline.initialize().unwrap();
+        pipeline.start().unwrap();
+        let mut events = pipeline.vad_events(LagPolicy::SkipForward);
+
+        // 上游20ms帧从流中第1秒开始
+        let start = Instant::now();
+        let frame = |n: u64, level: AudioSample| AudioFrame::at(AudioFormat::PIPELINE, vec![level; 320], start, 16000 + n * 320);
+
+        // 静音帧不送去识别
+        for n in 0..6 {
+            pipeline.process_audio_frame(&frame(n, 0));
+        }
+        assert_eq!(partials.load(Ordering::SeqCst), 0);
+
+        // 语音帧送去识别，语音边界沿用上游帧的采样序号
+        for n in 6..12 {
+            pipeline.process_audio_frame(&frame(n, 3000));
+        }
+        assert!(partials.load(Ordering::SeqCst) > 0);
+        assert_eq!(events.next().await, Some(Ok(crate::engine::vad::VadEvent::SpeechStart { sample_offset: 17920 })));
+    }
+
+    #[tokio::test]
+    async fn test_split_segments_reach_translation() {
+        use std::sync::atomic::AtomicUsize;
+
+        let config = VadConfig {
+            max_silence_duration_ms: 90,
+            max_segment_duration_ms: 600,
+            split_search_ms: 300,
+            ..Default::default()
+        };
+        let mut pipeline = TranslationPipeline::with_vad_config(
+            "./models/whisper-tiny.bin".to_string(),
+            "./models/qwen2.5-0.5b.bin".to_string(),
+            &config,
+        ).unwrap();
+        let splits = Arc::new(AtomicUsize::new(0));
+        let finals = Arc::new(AtomicUsize::new(0));
+        let (split_counter, final_counter) = (Arc::clone(&splits), Arc::clone(&finals));
+        pipeline.set_translation_callback(move |result| {
+            if result.is_partial {
+                return;
+            }
+            let counter = if result.is_final { &final_counter } else { &split_counter };
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        pipeline.initialize().unwrap();
+        pipeline.start().unwrap();
+
+        // 1.5秒不停顿的独白：超过600ms就强制切分，切出的部分不等语音结束就送去翻译，但不是最终结果
+        pipeline.process_stream(&vec![0; 480 * 10]);
+        pipeline.process_stream(&vec![3000; 480 * 50]);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let split = splits.load(Ordering::SeqCst);
+        assert!(split >= 2);
+        assert_eq!(finals.load(Ordering::SeqCst), 0);
+
+        // 语音结束后剩余部分作为最终语音段送去翻译
+        pipeline.process_stream(&vec![0; 480 * 5]);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(splits.load(Ordering::SeqCst), split);
+        assert_eq!(finals.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,23 +1,48 @@
 //! 文本转语音（Text-to-Speech）模块
 //! 将翻译结果转换为语音输出（可选功能）
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use crate::AudioFormat;
+use crate::dsp::loudness::{LoudnessConfig, LoudnessNormalizer};
+use crate::dsp::time_stretch::TimeStretcher;
 
 /// TTS结果结构
 #[derive(Debug, Clone)]
 pub struct TtsResult {
     pub text: String,                       // 输入文本
     pub audio_data: Vec<i16>,               // 生成的音频数据
+    pub format: AudioFormat,                // 音频数据的格式
     pub success: bool,                      // 是否成功
     pub timestamp: Instant,                 // 时间戳
 }
 
+impl TtsResult {
+    /// 语音时长
+    pub fn duration(&self) -> Duration {
+        self.format.duration_of(self.audio_data.len())
+    }
+
+    /// 变速不变调（`rate` > 1 加快），用于追赶播放积压
+    pub fn time_stretch(&mut self, rate: f32) {
+        let stretcher = TimeStretcher::new(self.format.sample_rate);
+        self.audio_data = stretcher.stretch_audio(&self.audio_data, rate);
+    }
+
+    /// 伸缩到指定时长
+    pub fn fit_to_duration(&mut self, target: Duration) {
+        let stretcher = TimeStretcher::new(self.format.sample_rate);
+        let length = self.format.samples_for_ms(target.as_millis() as u32);
+        self.audio_data = stretcher.stretch_audio_to_length(&self.audio_data, length);
+    }
+}
+
 /// 文本转语音器
 pub struct Tts {
     model_path: String,
     model_type: String,
     initialized: bool,
     model_loaded: bool,
+    loudness: Option<LoudnessNormalizer>,  // 输出前的响度归一化（None 表示保持模型原始电平）
 }
 
 impl Tts {
@@ -28,6 +53,7 @@ impl Tts {
             model_type,
             initialized: false,
             model_loaded: false,
+            loudness: Some(LoudnessNormalizer::new(AudioFormat::PIPELINE.sample_rate)),
         }
     }
 
@@ -59,6 +85,7 @@ impl Tts {
             return TtsResult {
                 text: text.to_string(),
                 audio_data: vec![],
+                format: self.output_format(),
                 success: false,
                 timestamp: Instant::now(),
             };
@@ -66,16 +93,38 @@ impl Tts {
         
         // 在实际实现中，这里会调用底层的TTS模型生成音频
         // 目前返回模拟结果
-        let audio_data = vec![0i16; 1600]; // 模拟100ms的音频数据 (16kHz采样率)
+        let mut audio_data = vec![0i16; 1600]; // 模拟100ms的音频数据 (16kHz采样率)
+
+        // 不同音色/模型的输出电平差异很大，注入虚拟麦克风或耳机之前统一到目标响度
+        if let Some(ref normalizer) = self.loudness {
+            normalizer.normalize_audio(&mut audio_data);
+        }
         
         TtsResult {
             text: text.to_string(),
             audio_data,
+            format: self.output_format(),
             success: true,
             timestamp: Instant::now(),
         }
     }
 
+    /// 生成语音的输出格式
+    pub fn output_format(&self) -> AudioFormat {
+        AudioFormat::PIPELINE
+    }
+
+    /// 设置输出响度归一化参数，None 关闭归一化
+    pub fn set_loudness_normalization(&mut self, config: Option<LoudnessConfig>) {
+        let sample_rate = self.output_format().sample_rate;
+        self.loudness = config.map(|config| LoudnessNormalizer::with_config(sample_rate, config));
+    }
+
+    /// 当前的输出响度归一化参数
+    pub fn loudness_normalization(&self) -> Option<&LoudnessConfig> {
+        self.loudness.as_ref().map(|normalizer| normalizer.config())
+    }
+
     /// 检查是否已初始化
     pub fn is_initialized(&self) -> bool {
         self.initialized
@@ -96,4 +145,37 @@ mod tests {
         let tts = Tts::new("./models/chattts.bin".to_string(), "chattts".to_string());
         assert_eq!(tts.model_type, "chattts");
     }
+
+    #[test]
+    fn test_tts_loudness_normalization_defaults_to_ebu_r128() {
+        let mut tts = Tts::new("./models/chattts.bin".to_string(), "chattts".to_string());
+        let config = tts.loudness_normalization().unwrap();
+        assert_eq!(config.target_lufs, -23.0);
+        assert_eq!(config.true_peak_db, -1.0);
+
+        // 静音输出不会被放大
+        tts.initialize().unwrap();
+        let result = tts.generate_speech("hello");
+        assert!(result.audio_data.iter().all(|&s| s == 0));
+
+        tts.set_loudness_normalization(None);
+        assert!(tts.loudness_normalization().is_none());
+    }
+
+    #[test]
+    fn test_tts_result_fits_time_window() {
+        let mut result = TtsResult {
+            text: "translated".to_string(),
+            audio_data: (0..32000).map(|i| ((i as f32 * 0.08).sin() * 8000.0) as i16).collect(),
+            format: AudioFormat::PIPELINE,
+            success: true,
+            timestamp: Instant::now(),
+        };
+        assert_eq!(result.duration(), Duration::from_secs(2));
+
+        result.fit_to_duration(Duration::from_millis(1500));
+        assert_eq!(result.duration(), Duration::from_millis(1500));
+        result.time_stretch(1.5);
+        assert_eq!(result.duration(), Duration::from_secs(1));
+    }
 }
\ No newline at end of file
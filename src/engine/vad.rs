@@ -1,9 +1,21 @@
 //! 语音活动检测（Voice Activity Detection）模块
 //! 用于检测音频流中的语音活动，过滤静音段，减少不必要的推理
 
+use std::collections::VecDeque;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
 use std::time::Instant;
-use crate::{AudioSample, SAMPLE_RATE, FRAME_SIZE_MS};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::Stream;
+use crate::core::sample::Sample;
+use crate::core::tap_buffer::LagPolicy;
+use crate::engine::gmm_vad::GmmVad;
+use crate::engine::silero_vad::SileroVad;
+use crate::{AudioBuffer, AudioFormat, AudioFrame, AudioSample, FormatMismatch, SAMPLE_RATE};
 
 /// VAD决策枚举
 #[derive(Debug, Clone, PartialEq)]
@@ -20,6 +32,8 @@ pub struct VadResult {
     pub probability: f32,        // 语音概率 (0.0-1.0)
     pub is_start_of_speech: bool,  // 是否为语音开始
     pub is_end_of_speech: bool,    // 是否为语音结束
+    pub sample_index: u64,         // 该帧首个采样在流中的序号
+    pub noise_floor_db: Option<f32>, // 检测引擎估计的背景噪声底（dBFS）
     pub timestamp: Instant,
 }
 
@@ -29,7 +43,278 @@ pub type AudioSegment = Vec<AudioSample>;
 /// 语音段结束回调函数类型
 pub type SpeechSegmentCallback = Box<dyn Fn(&AudioSegment, bool) + Send>;
 
+/// VAD事件，位置均为流中的采样序号，可与字幕、录音、说话人分离精确对齐
+///
+/// `SpeechStart` 在首个语音帧立即送出，不等语音达到最小持续时间；
+/// 语音帧累计不足 `min_speech_duration_ms` 的短促声音只有 `SpeechStart`/`SpeechEnd`，
+/// 两者之间没有 `Segment`，也不会回调语音段。
+#[derive(Debug, Clone, PartialEq)]
+pub enum VadEvent {
+    /// 语音开始：首个语音帧的起始位置（不含前补音频）
+    SpeechStart { sample_offset: u64 },
+    /// 语音结束：最后一个语音帧的结束位置，`duration` 为语音持续的采样数
+    SpeechEnd { sample_offset: u64, duration: u64 },
+    /// 语音段音频（含前后补入的音频），覆盖 `[start, end)`；强制切分的部分先于 `SpeechEnd` 送出
+    Segment { audio: AudioSegment, start: u64, end: u64 },
+}
+
+/// 每个订阅者最多缓存的事件数，落后更多时按 [`LagPolicy`] 处理
+pub const VAD_EVENT_CAPACITY: usize = 32;
+
+/// VAD事件流读取错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEventError {
+    Lagged { skipped: u64 },   // 订阅者落后，已跳过指定数量的事件
+    Detached { skipped: u64 }, // 订阅者因落后被断开，流随后结束
+}
+
+impl std::fmt::Display for VadEventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VadEventError::Lagged { skipped } => write!(f, "VAD event subscriber lagged, skipped {} events", skipped),
+            VadEventError::Detached { skipped } => write!(f, "VAD event subscriber detached after lagging {} events", skipped),
+        }
+    }
+}
+
+impl std::error::Error for VadEventError {}
+
+/// VAD事件流，缓存有界；订阅者落后时 `SkipForward` 报告一次 [`VadEventError::Lagged`] 后从最旧的事件继续，
+/// `Drop` 报告 [`VadEventError::Detached`] 后结束
+pub struct VadEventStream {
+    inner: Option<BroadcastStream<VadEvent>>,
+    policy: LagPolicy,
+}
+
+impl Stream for VadEventStream {
+    type Item = Result<VadEvent, VadEventError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let inner = match self.inner.as_mut() {
+            Some(inner) => inner,
+            None => return Poll::Ready(None),
+        };
+
+        match ready!(Pin::new(inner).poll_next(cx)) {
+            Some(Ok(event)) => Poll::Ready(Some(Ok(event))),
+            Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => match self.policy {
+                LagPolicy::SkipForward => Poll::Ready(Some(Err(VadEventError::Lagged { skipped }))),
+                LagPolicy::Drop => {
+                    self.inner = None;
+                    Poll::Ready(Some(Err(VadEventError::Detached { skipped })))
+                }
+            },
+            None => {
+                self.inner = None;
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+/// 语音检测引擎：逐帧给出语音概率，分段逻辑由 [`Vad`] 统一处理
+pub trait VadEngine: Send {
+    /// 引擎名称
+    fn name(&self) -> &str;
+
+    /// 计算一帧音频的语音概率 (0.0-1.0)
+    fn speech_probability(&mut self, frame: &[AudioSample]) -> f32;
+
+    /// 当前背景噪声底估计（dBFS），不跟踪噪声底的引擎返回None
+    fn noise_floor_db(&self) -> Option<f32> {
+        None
+    }
+
+    /// 重置内部状态（新的音频流开始时调用）
+    fn reset(&mut self);
+}
+
+/// 可选的检测引擎
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VadEngineKind {
+    Energy,  // 均方能量阈值
+    Gmm,     // WebRTC风格的高斯混合模型（子带能量 + 过零率）
+    Silero,  // Silero VAD 神经网络模型
+}
+
+/// VAD配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    pub engine: VadEngineKind,
+    pub model_path: String,           // Silero 官方ONNX模型文件
+    pub frame_ms: u32,
+    pub energy_threshold_db: f32,     // 能量引擎的语音阈值：高出噪声底的dB数
+    pub noise_floor_window_ms: u32,   // 能量引擎噪声底最小值跟踪的窗口长度
+    pub initial_noise_floor_db: f32,  // 跟踪窗口填满前假定的噪声底（dBFS）
+    pub start_threshold: f32,         // 语音概率达到该值时开始语音段
+    pub end_threshold: f32,           // 语音段中概率低于该值才计为静音（滞回）
+    pub min_speech_duration_ms: u32,
+    pub max_silence_duration_ms: u32,
+    pub pre_roll_ms: u32,             // 语音开始前保留并补入语音段的音频
+    pub post_roll_ms: u32,            // 语音结束后补入语音段的静音
+    pub max_segment_duration_ms: u32, // 语音段最长持续时间，超过后强制切分（0为不限制）
+    pub split_search_ms: u32,         // 强制切分时在语音段末尾搜索能量最低点的范围
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        VadConfig {
+            engine: VadEngineKind::Energy,
+            model_path: String::new(),
+            frame_ms: 30,
+            energy_threshold_db: 10.0,
+            noise_floor_window_ms: 2000,
+            initial_noise_floor_db: -60.0,
+            start_threshold: 0.5,
+            end_threshold: 0.35,
+            min_speech_duration_ms: 300,
+            max_silence_duration_ms: 800,
+            pre_roll_ms: 200,
+            post_roll_ms: 150,
+            max_segment_duration_ms: 10_000,
+            split_search_ms: 1000,
+        }
+    }
+}
+
+/// 按配置创建检测引擎
+pub fn create_vad_engine(config: &VadConfig, sample_rate: u32) -> Result<Box<dyn VadEngine>, Box<dyn std::error::Error>> {
+    Ok(match config.engine {
+        VadEngineKind::Energy => Box::new(EnergyVad::from_config(config, sample_rate)),
+        VadEngineKind::Gmm => Box::new(GmmVad::new(sample_rate)),
+        VadEngineKind::Silero => {
+            if config.model_path.is_empty() {
+                return Err("Silero VAD requires model_path".into());
+            }
+            Box::new(SileroVad::load(&config.model_path).map_err(|e| e.to_string())?)
+        }
+    })
+}
+
+/// 能量引擎的噪声底最小值跟踪窗口分成的子窗口数
+const FLOOR_SUBWINDOWS: usize = 4;
+/// 平滑后能量的最小值低估了平均噪声功率，乘以该偏置补偿（约1.8dB）
+const FLOOR_BIAS: f64 = 1.5;
+/// 帧能量平滑系数
+const LEVEL_SMOOTHING: f64 = 0.7;
+/// 噪声底下限（dBFS），数字静音不会把噪声底拉到负无穷
+const MIN_NOISE_FLOOR_DB: f32 = -80.0;
+
+/// 均方能量检测引擎
+///
+/// 以最小值统计持续跟踪背景噪声底：平滑后的帧能量在滑动窗口内的最小值即为噪声底，
+/// 窗口按子窗口滚动，噪声变小时噪声底随平滑能量下降，变大时最多一个窗口后跟上。
+/// 能量高出噪声底 `threshold_db` 时概率为0.5，每再高出3dB概率按逻辑函数上升。
+pub struct EnergyVad {
+    threshold_db: f32,            // 语音阈值：高出噪声底的dB数
+    initial_floor_db: f32,        // 窗口填满前假定的噪声底
+    subwindow_samples: usize,     // 每个子窗口的采样数
+    smoothed_power: Option<f64>,  // 平滑后的均方能量（满量程为1）
+    subwindow_min: f64,           // 当前子窗口内的最小能量
+    subwindow_filled: usize,
+    minima: [f64; FLOOR_SUBWINDOWS], // 最近几个已完成子窗口的最小能量
+    next_minimum: usize,
+}
+
+impl EnergyVad {
+    /// 创建能量检测引擎（默认的噪声底跟踪参数）
+    pub fn new(sample_rate: u32, threshold_db: f32) -> Self {
+        let config = VadConfig { energy_threshold_db: threshold_db, ..VadConfig::default() };
+        Self::from_config(&config, sample_rate)
+    }
+
+    /// 按配置创建能量检测引擎
+    pub fn from_config(config: &VadConfig, sample_rate: u32) -> Self {
+        let window_samples = config.noise_floor_window_ms as usize * sample_rate as usize / 1000;
+        let mut vad = EnergyVad {
+            threshold_db: config.energy_threshold_db,
+            initial_floor_db: config.initial_noise_floor_db,
+            subwindow_samples: (window_samples / FLOOR_SUBWINDOWS).max(1),
+            smoothed_power: None,
+            subwindow_min: f64::MAX,
+            subwindow_filled: 0,
+            minima: [0.0; FLOOR_SUBWINDOWS],
+            next_minimum: 0,
+        };
+        vad.reset();
+        vad
+    }
+
+    /// 当前噪声底估计（dBFS）
+    pub fn noise_floor_db(&self) -> f32 {
+        let minimum = self.minima.iter().fold(self.subwindow_min, |a, &b| a.min(b));
+        let floor_db = 10.0 * (minimum * FLOOR_BIAS).max(1e-12).log10();
+        (floor_db as f32).max(MIN_NOISE_FLOOR_DB)
+    }
+
+    /// 语音阈值（高出噪声底的dB数）
+    pub fn threshold_db(&self) -> f32 {
+        self.threshold_db
+    }
+}
+
+impl VadEngine for EnergyVad {
+    fn name(&self) -> &str {
+        "energy"
+    }
+
+    fn speech_probability(&mut self, frame: &[AudioSample]) -> f32 {
+        if frame.is_empty() {
+            return 0.0;
+        }
+        // 计算音频能量（满量程为1）
+        let power: f64 = frame
+            .iter()
+            .map(|sample| {
+                let x = sample.to_f32() as f64;
+                x * x
+            })
+            .sum::<f64>() / frame.len() as f64;
+        let level_db = 10.0 * power.max(1e-12).log10();
+        // 判决使用更新前的噪声底，语音起始帧不会抬高自己的参考
+        let db_above = level_db as f32 - self.noise_floor_db() - self.threshold_db;
+
+        let smoothed = match self.smoothed_power {
+            Some(previous) => LEVEL_SMOOTHING * previous + (1.0 - LEVEL_SMOOTHING) * power,
+            None => power,
+        };
+        self.smoothed_power = Some(smoothed);
+        self.subwindow_min = self.subwindow_min.min(smoothed);
+        self.subwindow_filled += frame.len();
+        if self.subwindow_filled >= self.subwindow_samples {
+            self.minima[self.next_minimum] = self.subwindow_min;
+            self.next_minimum = (self.next_minimum + 1) % FLOOR_SUBWINDOWS;
+            self.subwindow_min = f64::MAX;
+            self.subwindow_filled = 0;
+        }
+
+        1.0 / (1.0 + (-db_above / 3.0).exp())
+    }
+
+    fn noise_floor_db(&self) -> Option<f32> {
+        Some(EnergyVad::noise_floor_db(self))
+    }
+
+    fn reset(&mut self) {
+        let initial = 10f64.powf(self.initial_floor_db as f64 / 10.0) / FLOOR_BIAS;
+        self.minima = [initial; FLOOR_SUBWINDOWS];
+        self.next_minimum = 0;
+        self.subwindow_min = f64::MAX;
+        self.subwindow_filled = 0;
+        self.smoothed_power = None;
+    }
+}
+
 /// 语音活动检测器
+///
+/// 检测引擎给出逐帧语音概率，这里做滞回判决并切分语音段：
+/// 概率达到 `start_threshold` 开始语音段，之后低于 `end_threshold` 的帧才计为静音。
+/// 语音段前补入开始前 `pre_roll` 的历史音频（保留词首的爆破音、轻辅音），
+/// 段内的静音原样保留，结束时再补入 `post_roll` 的尾部静音。
+/// 一直不停顿的长语音段超过最长持续时间后，在末尾搜索范围内能量最低处切开，
+/// 前半段作为非最终语音段回调，后半段继续累积。
 pub struct Vad {
     sample_rate: u32,
     frame_duration_ms: u32,
@@ -39,25 +324,118 @@ pub struct Vad {
     max_silence_duration_ms: u32, // 最大静音持续时间
     silence_count_ms: u32,
     in_speech: bool,
-    energy_threshold: f64,  // 能量阈值
+    engine: Box<dyn VadEngine>,
+    start_threshold: f32,
+    end_threshold: f32,
+    samples_processed: u64, // 已处理的采样数（未携带采样时钟的输入使用）
+    pre_roll_samples: usize,
+    post_roll_samples: usize,
+    history: VecDeque<AudioSample>, // 语音段外最近的音频，用于前补
+    pending_silence: Vec<AudioSample>, // 语音段内尚未确定去留的静音帧
+    voiced_samples: usize,          // 当前语音段中语音帧的采样数（不含补入音频与段内静音）
+    max_segment_samples: usize,     // 0为不限制
+    split_search_samples: usize,
+    segment_continued: bool,        // 当前语音段是强制切分后的后续部分
+    segment_start: u64,             // 当前语音段首个采样的位置
+    speech_start: u64,              // 本次语音开始的位置
+    speech_end: u64,                // 最近一个语音帧的结束位置
+    event_sender: broadcast::Sender<VadEvent>,
 }
 
 impl Vad {
-    /// 创建新的VAD实例
+    /// 创建新的VAD实例（能量检测引擎）
     pub fn new(sample_rate: u32, frame_duration_ms: u32) -> Self {
-        Vad {
-            sample_rate,
-            frame_duration_ms,
+        Self::with_engine(
+            AudioFormat::new(sample_rate, 1, frame_duration_ms),
+            Box::new(EnergyVad::from_config(&VadConfig::default(), sample_rate)),
+        )
+    }
+
+    /// 使用指定检测引擎创建VAD实例
+    pub fn with_engine(format: AudioFormat, engine: Box<dyn VadEngine>) -> Self {
+        let config = VadConfig::default();
+        let mut vad = Vad {
+            sample_rate: format.sample_rate,
+            frame_duration_ms: format.frame_size_ms,
             segment_callback: None,
             current_segment: Arc::new(Mutex::new(Vec::new())),
-            min_speech_duration_ms: 300,  // 默认最小语音持续时间为300ms
-            max_silence_duration_ms: 800, // 默认最大静音持续时间为800ms
+            min_speech_duration_ms: config.min_speech_duration_ms,  // 默认最小语音持续时间为300ms
+            max_silence_duration_ms: config.max_silence_duration_ms, // 默认最大静音持续时间为800ms
             silence_count_ms: 0,
             in_speech: false,
-            energy_threshold: 1000.0,  // 默认能量阈值
+            engine,
+            start_threshold: config.start_threshold,
+            end_threshold: config.end_threshold,
+            samples_processed: 0,
+            pre_roll_samples: 0,
+            post_roll_samples: 0,
+            history: VecDeque::new(),
+            pending_silence: Vec::new(),
+            voiced_samples: 0,
+            max_segment_samples: 0,
+            split_search_samples: 0,
+            segment_continued: false,
+            segment_start: 0,
+            speech_start: 0,
+            speech_end: 0,
+            event_sender: broadcast::channel(VAD_EVENT_CAPACITY).0,
+        };
+        vad.set_max_segment_duration(config.max_segment_duration_ms, config.split_search_ms);
+        vad.set_padding(config.pre_roll_ms, config.post_roll_ms);
+        vad
+    }
+
+    /// 按配置创建VAD实例
+    pub fn from_config(config: &VadConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let engine = create_vad_engine(config, SAMPLE_RATE)?;
+        let mut vad = Self::with_engine(AudioFormat::new(SAMPLE_RATE, 1, config.frame_ms), engine);
+        vad.set_thresholds(config.start_threshold, config.end_threshold);
+        vad.min_speech_duration_ms = config.min_speech_duration_ms;
+        vad.max_silence_duration_ms = config.max_silence_duration_ms;
+        vad.set_padding(config.pre_roll_ms, config.post_roll_ms);
+        vad.set_max_segment_duration(config.max_segment_duration_ms, config.split_search_ms);
+        Ok(vad)
+    }
+
+    /// 按音频格式创建VAD实例（只支持单声道）
+    pub fn with_format(format: AudioFormat) -> Self {
+        Self::new(format.sample_rate, format.frame_size_ms)
+    }
+
+    /// VAD期望的输入格式
+    pub fn format(&self) -> AudioFormat {
+        AudioFormat::new(self.sample_rate, 1, self.frame_duration_ms)
+    }
+
+    /// 替换检测引擎（分段状态保持不变）
+    pub fn set_engine(&mut self, engine: Box<dyn VadEngine>) {
+        self.engine = engine;
+    }
+
+    /// 当前检测引擎名称
+    pub fn engine_name(&self) -> &str {
+        self.engine.name()
+    }
+
+    /// 设置滞回阈值：开始阈值不低于结束阈值
+    pub fn set_thresholds(&mut self, start: f32, end: f32) {
+        self.start_threshold = start.clamp(0.0, 1.0);
+        self.end_threshold = end.clamp(0.0, self.start_threshold);
+    }
+
+    /// 订阅VAD事件流，丢弃返回的流即取消订阅；落后超过 [`VAD_EVENT_CAPACITY`] 个事件时按 `policy` 处理
+    pub fn events(&mut self, policy: LagPolicy) -> VadEventStream {
+        VadEventStream {
+            inner: Some(BroadcastStream::new(self.event_sender.subscribe())),
+            policy,
         }
     }
 
+    /// 向所有订阅者发送事件，没有订阅者时直接丢弃
+    fn emit(&mut self, event: VadEvent) {
+        let _ = self.event_sender.send(event);
+    }
+
     /// 设置语音段结束回调函数
     pub fn set_speech_segment_callback(&mut self, callback: SpeechSegmentCallback) {
         self.segment_callback = Some(callback);
@@ -65,70 +443,163 @@ impl Vad {
 
     /// 处理音频帧
     pub fn process_frame(&mut self, audio_frame: &[AudioSample]) -> VadResult {
-        // 计算音频能量
-        let energy: f64 = audio_frame
-            .iter()
-            .map(|&sample| (sample as f64) * (sample as f64))
-            .sum::<f64>() / audio_frame.len() as f64;
-
-        // 简单的能量阈值判断
-        let is_speech = energy > self.energy_threshold;
+        let probability = self.engine.speech_probability(audio_frame);
+        let decision = if probability >= self.start_threshold {
+            VadDecision::Speech
+        } else if probability < self.end_threshold {
+            VadDecision::Silence
+        } else {
+            VadDecision::Unclear
+        };
+        // 滞回：语音段外需要达到开始阈值，语音段内只要不低于结束阈值
+        let is_speech = if self.in_speech {
+            probability >= self.end_threshold
+        } else {
+            probability >= self.start_threshold
+        };
 
         let mut result = VadResult {
-            decision: if is_speech { VadDecision::Speech } else { VadDecision::Silence },
-            probability: ((energy / (self.energy_threshold * 5.0)).min(1.0)) as f32,
+            decision,
+            probability,
             is_start_of_speech: false,
             is_end_of_speech: false,
+            sample_index: self.samples_processed,
+            noise_floor_db: self.engine.noise_floor_db(),
             timestamp: Instant::now(),
         };
+        let frame_start = self.samples_processed;
+        self.samples_processed += audio_frame.len() as u64;
 
         if is_speech {
-            if !self.in_speech {
+            let mut segment = self.current_segment.lock().unwrap();
+            let started = !self.in_speech;
+            if started {
                 // 语音开始
                 result.is_start_of_speech = true;
                 self.in_speech = true;
-                self.silence_count_ms = 0;
 
-                // 开始新的语音段
-                self.current_segment.lock().unwrap().clear();
+                // 开始新的语音段，先补入开始前的历史音频
+                segment.clear();
+                segment.extend(self.history.drain(..));
+                self.voiced_samples = 0;
+                self.segment_start = frame_start.saturating_sub(segment.len() as u64);
+                self.speech_start = frame_start;
             }
+            self.silence_count_ms = 0;
+            self.speech_end = self.samples_processed;
+            self.voiced_samples += audio_frame.len();
 
-            // 将当前帧添加到语音段
-            self.current_segment.lock().unwrap().extend_from_slice(audio_frame);
-        } else {
-            if self.in_speech {
-                // 语音结束检测
-                self.silence_count_ms += self.frame_duration_ms;
-
-                if self.silence_count_ms >= self.max_silence_duration_ms {
-                    // 检测到语音结束
-                    result.is_end_of_speech = true;
-
-                    // 检查语音段是否满足最小持续时间要求
-                    let speech_duration_ms = 
-                        (self.current_segment.lock().unwrap().len() * 1000) as u32 / self.sample_rate;
-                    
-                    if speech_duration_ms >= self.min_speech_duration_ms {
-                        if let Some(ref callback) = self.segment_callback {
-                            let segment = self.current_segment.lock().unwrap().clone();
-                            callback(&segment, true); // final segment
-                        }
-                    }
-
-                    self.in_speech = false;
-                    self.current_segment.lock().unwrap().clear();
-                }
+            // 段内的静音随后续语音一起保留，然后添加当前帧
+            segment.append(&mut self.pending_silence);
+            segment.extend_from_slice(audio_frame);
+            let too_long = self.max_segment_samples > 0 && segment.len() > self.max_segment_samples;
+            drop(segment);
+            if started {
+                self.emit(VadEvent::SpeechStart { sample_offset: frame_start });
+            }
+            if too_long {
+                self.split_segment();
+            }
+        } else if self.in_speech {
+            // 语音结束检测
+            self.silence_count_ms += self.frame_duration_ms;
+            self.pending_silence.extend_from_slice(audio_frame);
+
+            if self.silence_count_ms >= self.max_silence_duration_ms {
+                // 检测到语音结束
+                result.is_end_of_speech = true;
+                self.finish_segment();
             }
+        } else {
+            self.push_history(audio_frame);
         }
 
         result
     }
 
+    /// 结束当前语音段：满足最小持续时间时补入尾部静音并回调
+    fn finish_segment(&mut self) {
+        let mut segment = std::mem::take(&mut *self.current_segment.lock().unwrap());
+
+        // 检查语音段是否满足最小持续时间要求（只计语音帧，不计补入的音频和段内静音）
+        let speech_duration_ms = (self.voiced_samples as u64 * 1000 / self.sample_rate as u64) as u32;
+        let post_roll = self.post_roll_samples.min(self.pending_silence.len());
+        // 强制切分后的剩余部分无论多短都要送出，下游据此结束这句话
+        if speech_duration_ms >= self.min_speech_duration_ms || self.segment_continued {
+            segment.extend_from_slice(&self.pending_silence[..post_roll]);
+            if let Some(ref callback) = self.segment_callback {
+                callback(&segment, true); // final segment
+            }
+            if self.event_sender.receiver_count() > 0 {
+                let end = self.segment_start + segment.len() as u64;
+                self.emit(VadEvent::Segment { audio: segment, start: self.segment_start, end });
+            }
+        }
+        self.emit(VadEvent::SpeechEnd {
+            sample_offset: self.speech_end,
+            duration: self.speech_end - self.speech_start,
+        });
+
+        // 结束后的静音成为下一个语音段的历史音频
+        self.in_speech = false;
+        self.segment_continued = false;
+        self.history.clear();
+        let silence = std::mem::take(&mut self.pending_silence);
+        self.push_history(&silence);
+    }
+
+    /// 强制切分过长的语音段：在末尾搜索范围内找10ms能量最低的位置，前半段作为非最终语音段回调
+    fn split_segment(&mut self) {
+        let head = {
+            let mut segment = self.current_segment.lock().unwrap();
+            let split = lowest_energy_point(&segment, self.split_search_samples, self.sample_rate as usize / 100);
+            let tail = segment.split_off(split);
+            std::mem::replace(&mut *segment, tail)
+        };
+        if let Some(ref callback) = self.segment_callback {
+            callback(&head, false);
+        }
+        let start = self.segment_start;
+        self.segment_start += head.len() as u64;
+        if self.event_sender.receiver_count() > 0 {
+            self.emit(VadEvent::Segment { audio: head, start, end: self.segment_start });
+        }
+        self.segment_continued = true;
+    }
+
+    /// 把语音段外的音频存入历史缓冲区，只保留最近的前补长度
+    fn push_history(&mut self, audio: &[AudioSample]) {
+        self.history.extend(audio.iter().copied());
+        let excess = self.history.len().saturating_sub(self.pre_roll_samples);
+        self.history.drain(..excess);
+    }
+
+    /// 处理分帧器输出的音频帧，结果使用帧的采样时钟而不是处理时刻
+    pub fn process_audio_frame(&mut self, frame: &AudioFrame) -> Result<VadResult, FormatMismatch> {
+        self.format().check_compatible(&frame.format)?;
+        self.samples_processed = frame.sample_index;
+        let mut result = self.process_frame(&frame.samples);
+        result.timestamp = frame.timestamp;
+        Ok(result)
+    }
+
+    /// 处理带格式信息的音频缓冲区，格式与VAD不一致时返回错误
+    pub fn process_buffer(&mut self, buffer: &AudioBuffer) -> Result<VadResult, FormatMismatch> {
+        self.format().check_compatible(&buffer.format)?;
+        Ok(self.process_frame(&buffer.samples))
+    }
+
     /// 重置VAD状态
     pub fn reset(&mut self) {
         self.current_segment.lock().unwrap().clear();
         self.silence_count_ms = 0;
         self.in_speech = false;
+        self.samples_processed = 0;
+        self.history.clear();
+        self.pending_silence.clear();
+        self.voiced_samples = 0;
+        self.segment_continued = false;
+        self.engine.reset();
     }
 
     /// 获取当前累积的语音段
@@ -145,6 +616,36 @@ impl Vad {
     pub fn set_max_silence_duration(&mut self, duration_ms: u32) {
         self.max_silence_duration_ms = duration_ms;
     }
+
+    /// 设置语音段最长持续时间与切分点搜索范围（毫秒），最长持续时间为0时不强制切分
+    pub fn set_max_segment_duration(&mut self, max_duration_ms: u32, search_ms: u32) {
+        self.max_segment_samples = (max_duration_ms as u64 * self.sample_rate as u64 / 1000) as usize;
+        self.split_search_samples = (search_ms as u64 * self.sample_rate as u64 / 1000) as usize;
+    }
+
+    /// 设置语音段前后补入的音频长度（毫秒）
+    pub fn set_padding(&mut self, pre_roll_ms: u32, post_roll_ms: u32) {
+        self.pre_roll_samples = (pre_roll_ms as u64 * self.sample_rate as u64 / 1000) as usize;
+        self.post_roll_samples = (post_roll_ms as u64 * self.sample_rate as u64 / 1000) as usize;
+        let excess = self.history.len().saturating_sub(self.pre_roll_samples);
+        self.history.drain(..excess);
+    }
+}
+
+/// 在音频末尾 `search` 个采样范围内，按 `block` 长度分块找能量最低的块，返回其中心位置
+fn lowest_energy_point(audio: &[AudioSample], search: usize, block: usize) -> usize {
+    let block = block.max(1);
+    let start = audio.len().saturating_sub(search.max(block));
+    audio[start..]
+        .chunks(block)
+        .enumerate()
+        .filter(|(_, chunk)| chunk.len() == block)
+        .map(|(i, chunk)| {
+            let energy: f64 = chunk.iter().map(|&x| (x as f64) * (x as f64)).sum();
+            (start + i * block + block / 2, energy)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(audio.len(), |(position, _)| position)
 }
 
 #[cfg(test)]
@@ -168,4 +669,323 @@ mod tests {
         let result = vad.process_frame(&silent_frame);
         assert_eq!(result.decision, VadDecision::Silence);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_vad_rejects_mismatched_format() {
+        let mut vad = Vad::with_format(AudioFormat::new(16000, 1, 30));
+        assert_eq!(vad.format().samples_per_frame(), 480);
+
+        let ok = AudioBuffer::silence(AudioFormat::new(16000, 1, 30), 30);
+        assert!(vad.process_buffer(&ok).is_ok());
+
+        // 48kHz立体声缓冲区在进入VAD时就被拒绝，而不是被当作错误的能量计算
+        let wrong = AudioBuffer::silence(AudioFormat::new(48000, 2, 30), 30);
+        let err = vad.process_buffer(&wrong).unwrap_err();
+        assert_eq!(err.expected.sample_rate, 16000);
+        assert_eq!(err.actual.channels, 2);
+    }
+
+    #[test]
+    fn test_vad_uses_frame_sample_clock() {
+        let format = AudioFormat::new(16000, 1, 30);
+        let mut vad = Vad::with_format(format);
+        let mut framer = crate::core::framer::Framer::new(format);
+
+        let frames = framer.push(&vec![0; 480 * 3 + 100]);
+        let results: Vec<VadResult> = frames
+            .iter()
+            .map(|frame| vad.process_audio_frame(frame).unwrap())
+            .collect();
+
+        assert_eq!(results[2].sample_index, 960);
+        assert_eq!(results[2].timestamp, framer.stream_start() + std::time::Duration::from_millis(60));
+        // 未携带采样时钟的输入从上一帧结束处继续计数
+        assert_eq!(vad.process_frame(&[0; 480]).sample_index, 1440);
+    }
+
+    #[test]
+    fn test_vad_uses_silero_probability() {
+        // 输出恒为0.98的模型：30ms的VAD帧凑满512个采样的模型窗口后才有概率
+        let model = crate::engine::silero_vad::tests::constant_model(0.98);
+        let mut vad = Vad::with_engine(AudioFormat::new(16000, 1, 30), Box::new(model));
+        assert_eq!(vad.engine_name(), "silero");
+        assert_eq!(vad.process_frame(&[0; 480]).decision, VadDecision::Silence);
+        let result = vad.process_frame(&[0; 480]);
+        assert_eq!(result.decision, VadDecision::Speech);
+        assert!((result.probability - 0.98).abs() < 0.001);
+
+        vad.set_thresholds(0.99, 0.99);
+        assert_eq!(vad.process_frame(&[0; 480]).decision, VadDecision::Silence);
+    }
+
+    #[test]
+    fn test_engine_from_config_with_hysteresis() {
+        let config: VadConfig = serde_json::from_str(r#"{"engine": "gmm"}"#).unwrap();
+        assert_eq!(config.frame_ms, 30);
+        assert_eq!(Vad::from_config(&config).unwrap().engine_name(), "gmm");
+        let silero = VadConfig { engine: VadEngineKind::Silero, ..Default::default() };
+        assert!(Vad::from_config(&silero).is_err());
+
+        let mut vad = Vad::from_config(&VadConfig { max_silence_duration_ms: 90, ..Default::default() }).unwrap();
+        assert_eq!(vad.engine_name(), "energy");
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&segments);
+        vad.set_speech_segment_callback(Box::new(move |segment, _| sink.lock().unwrap().push(segment.len())));
+
+        // 数字静音把噪声底压到下限-80dBFS，语音阈值即为-70dBFS
+        let (loud, medium, quiet) = ([100i16; 480], [9i16; 480], [0i16; 480]);
+        assert_eq!(vad.process_frame(&quiet).noise_floor_db, Some(-80.0));
+
+        // 介于两个阈值之间的帧不会开始语音段，但能延续已开始的语音段
+        let result = vad.process_frame(&medium);
+        assert_eq!(result.decision, VadDecision::Unclear);
+        assert!(!result.is_start_of_speech);
+        assert!(vad.process_frame(&loud).is_start_of_speech);
+        for _ in 0..10 {
+            assert!(!vad.process_frame(&medium).is_end_of_speech);
+        }
+        // 语音段开头带有开始前的两帧历史音频
+        assert_eq!(vad.get_current_speech_segment().len(), 480 * 13);
+
+        let ends: Vec<bool> = (0..3).map(|_| vad.process_frame(&quiet).is_end_of_speech).collect();
+        assert_eq!(ends, vec![false, false, true]);
+        assert_eq!(*segments.lock().unwrap(), vec![480 * 16]);
+    }
+
+    #[test]
+    fn test_energy_threshold_tracks_noise_floor() {
+        let mut rng = crate::core::rng::XorShift32::new(7);
+        let mut noisy_frame = move |gain: f32, voiced: bool| -> Vec<AudioSample> {
+            (0..480)
+                .map(|i| {
+                    let noise = rng.next_signed() * 0.035 * gain;  // 约 -34 dBFS
+                    let voice = if voiced { 0.3 * (i as f32 * 0.1).sin() } else { 0.0 };
+                    ((noise + voice) * 32767.0) as AudioSample
+                })
+                .collect()
+        };
+
+        // 嘈杂房间：固定阈值会把背景噪声一直判为语音，噪声底跟上后不会
+        let mut vad = Vad::new(16000, 30);
+        let results: Vec<VadResult> = (0..100).map(|_| vad.process_frame(&noisy_frame(1.0, false))).collect();
+        assert_eq!(results[0].decision, VadDecision::Speech);
+        assert!(results[80..].iter().all(|r| r.probability < 0.1), "{:?}", results[80].probability);
+        let floor = results[99].noise_floor_db.unwrap();
+        assert!((floor + 34.0).abs() < 2.0, "floor {floor}");
+
+        // 阈值相对噪声底：高出噪声约20dB的语音仍被检测到
+        assert!((0..10).all(|_| vad.process_frame(&noisy_frame(1.0, true)).probability > 0.9));
+
+        // 噪声变小时噪声底随平滑能量下降，不必等待整个跟踪窗口
+        let quiet = (0..40).map(|_| vad.process_frame(&noisy_frame(0.03, false))).last().unwrap();
+        assert!(quiet.noise_floor_db.unwrap() < -60.0, "{:?}", quiet.noise_floor_db);
+
+        vad.reset();
+        assert_eq!(vad.process_frame(&[0; 480]).noise_floor_db, Some(-80.0));
+    }
+
+    #[test]
+    fn test_segment_pre_roll_and_post_roll() {
+        let config = VadConfig { pre_roll_ms: 60, post_roll_ms: 60, max_silence_duration_ms: 150, ..Default::default() };
+        let mut vad = Vad::from_config(&config).unwrap();
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&segments);
+        vad.set_speech_segment_callback(Box::new(move |segment, _| sink.lock().unwrap().push(segment.clone())));
+
+        let quiet = |k: i16| [k % 3; 480];
+        let loud = [3000i16; 480];
+        for k in 0..10 {
+            vad.process_frame(&quiet(k));
+        }
+        let mut frames = vec![loud; 12];
+        frames.extend([quiet(10), quiet(11)]);
+        frames.extend([loud; 12]);
+        frames.extend((12..17).map(quiet));
+        let ends: Vec<bool> = frames.iter().map(|f| vad.process_frame(f).is_end_of_speech).collect();
+        assert_eq!(ends.iter().position(|&e| e), Some(30));
+
+        // 前补最近60ms，段内静音保留，结束后只补入60ms尾部静音
+        let segments = segments.lock().unwrap();
+        assert_eq!(segments.len(), 1);
+        let expected: Vec<AudioSample> = [quiet(8), quiet(9)].iter()
+            .chain(frames[..28].iter())
+            .flatten()
+            .copied()
+            .collect();
+        assert_eq!(segments[0], expected);
+    }
+
+    /// 非零即语音的检测引擎
+    struct NonZeroEngine;
+
+    impl VadEngine for NonZeroEngine {
+        fn name(&self) -> &str {
+            "nonzero"
+        }
+
+        fn speech_probability(&mut self, frame: &[AudioSample]) -> f32 {
+            if frame.iter().any(|&x| x != 0) { 1.0 } else { 0.0 }
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    #[test]
+    fn test_long_segment_split_at_lowest_energy() {
+        let config = VadConfig {
+            pre_roll_ms: 0,
+            post_roll_ms: 0,
+            max_segment_duration_ms: 3000,
+            split_search_ms: 1000,
+            max_silence_duration_ms: 90,
+            ..Default::default()
+        };
+        let mut vad = Vad::from_config(&config).unwrap();
+        vad.set_engine(Box::new(NonZeroEngine));
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&segments);
+        vad.set_speech_segment_callback(Box::new(move |segment, is_final| {
+            sink.lock().unwrap().push((segment.len(), is_final))
+        }));
+
+        // 不停顿的独白：第80帧音量较低但仍是语音
+        for i in 0..120 {
+            let level = if i == 80 { 500 } else { 3000 };
+            vad.process_frame(&[level; 480]);
+        }
+        // 超过3秒时在最后1秒内能量最低的10ms块中心切开
+        assert_eq!(*segments.lock().unwrap(), vec![(80 * 480 + 80, false)]);
+
+        for _ in 0..3 {
+            vad.process_frame(&[0; 480]);
+        }
+        assert_eq!(segments.lock().unwrap()[1], (120 * 480 - (80 * 480 + 80), true));
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_sample_positions() {
+        use tokio_stream::StreamExt;
+
+        let config = VadConfig {
+            pre_roll_ms: 60,
+            post_roll_ms: 60,
+            max_silence_duration_ms: 90,
+            max_segment_duration_ms: 600,
+            split_search_ms: 300,
+            ..Default::default()
+        };
+        let mut vad = Vad::from_config(&config).unwrap();
+        vad.set_engine(Box::new(NonZeroEngine));
+        let events = vad.events(LagPolicy::SkipForward);
+
+        let mut frames = vec![[0i16; 480]; 5];
+        frames.extend((0..25).map(|i| [if i == 16 { 1 } else { 3000 }; 480]));
+        frames.extend([[0; 480]; 3]);
+        for frame in &frames {
+            vad.process_frame(frame);
+        }
+        drop(vad);
+
+        let events: Vec<Result<VadEvent, VadEventError>> = events.collect().await;
+        let positions: Vec<String> = events.iter()
+            .map(|event| event.as_ref().unwrap())
+            .map(|event| match event {
+                VadEvent::SpeechStart { sample_offset } => format!("start {sample_offset}"),
+                VadEvent::SpeechEnd { sample_offset, duration } => format!("end {sample_offset} {duration}"),
+                VadEvent::Segment { audio, start, end } => {
+                    assert_eq!(audio.len() as u64, end - start);
+                    format!("segment {start}..{end}")
+                }
+            })
+            .collect();
+        // 语音从第5帧开始，前补60ms；超过600ms后在第16个语音帧中心切开；尾部补60ms
+        assert_eq!(positions, vec![
+            "start 2400",
+            "segment 1440..10160",
+            "segment 10160..15360",
+            "end 14400 12000",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_reports_lag() {
+        use tokio_stream::StreamExt;
+
+        let config = VadConfig { pre_roll_ms: 0, post_roll_ms: 0, max_silence_duration_ms: 30, ..Default::default() };
+        let mut vad = Vad::from_config(&config).unwrap();
+        vad.set_engine(Box::new(NonZeroEngine));
+        let skipping = vad.events(LagPolicy::SkipForward);
+        let dropped = vad.events(LagPolicy::Drop);
+
+        // 20次语音开始/结束共40个事件，超过缓存的32个
+        for _ in 0..20 {
+            vad.process_frame(&[3000; 480]);
+            vad.process_frame(&[0; 480]);
+        }
+        drop(vad);
+
+        // 跳过最旧的8个事件后从仍在缓存中的事件继续
+        let events: Vec<_> = skipping.collect().await;
+        assert_eq!(events.len(), 1 + VAD_EVENT_CAPACITY);
+        assert_eq!(events[0], Err(VadEventError::Lagged { skipped: 8 }));
+        assert_eq!(events[1], Ok(VadEvent::SpeechStart { sample_offset: 8 * 480 }));
+
+        // 断开的订阅者只收到一次错误，随后流结束
+        let events: Vec<_> = dropped.collect().await;
+        assert_eq!(events, vec![Err(VadEventError::Detached { skipped: 8 })]);
+    }
+
+    #[test]
+    fn test_min_speech_counts_voiced_frames_only() {
+        let config = VadConfig { pre_roll_ms: 0, post_roll_ms: 0, max_silence_duration_ms: 150, ..Default::default() };
+        let mut vad = Vad::from_config(&config).unwrap();
+        vad.set_engine(Box::new(NonZeroEngine));
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&segments);
+        vad.set_speech_segment_callback(Box::new(move |segment, _| sink.lock().unwrap().push(segment.len())));
+
+        // 4个30ms的短促声音被停顿隔开：语音段跨度超过300ms，但语音只有120ms
+        for _ in 0..4 {
+            vad.process_frame(&[1000; 480]);
+            vad.process_frame(&[0; 480]);
+            vad.process_frame(&[0; 480]);
+            vad.process_frame(&[0; 480]);
+        }
+        for _ in 0..2 {
+            vad.process_frame(&[0; 480]);
+        }
+        assert!(segments.lock().unwrap().is_empty());
+
+        for _ in 0..10 {
+            vad.process_frame(&[1000; 480]);
+        }
+        for _ in 0..5 {
+            vad.process_frame(&[0; 480]);
+        }
+        assert_eq!(*segments.lock().unwrap(), vec![480 * 10]);
+    }
+
+    #[tokio::test]
+    async fn test_short_blip_emits_start_and_end_without_segment() {
+        use tokio_stream::StreamExt;
+
+        let config = VadConfig { pre_roll_ms: 0, post_roll_ms: 0, max_silence_duration_ms: 60, ..Default::default() };
+        let mut vad = Vad::from_config(&config).unwrap();
+        vad.set_engine(Box::new(NonZeroEngine));
+        let events = vad.events(LagPolicy::SkipForward);
+
+        // 60ms的短促声音不足300ms的最小语音时长
+        vad.process_frame(&[0; 480]);
+        vad.process_frame(&[3000; 480]);
+        vad.process_frame(&[3000; 480]);
+        vad.process_frame(&[0; 480]);
+        vad.process_frame(&[0; 480]);
+        drop(vad);
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events, vec![
+            Ok(VadEvent::SpeechStart { sample_offset: 480 }),
+            Ok(VadEvent::SpeechEnd { sample_offset: 1440, duration: 960 }),
+        ]);
+    }
+}
@@ -4,6 +4,7 @@
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
+use crate::audio_types::AudioFormat;
 
 /// 音频设备信息结构
 #[derive(Debug, Clone)]
@@ -15,8 +16,15 @@ pub struct DeviceInfo {
     pub is_default: bool,
 }
 
-/// 音频采样类型
-pub type AudioSample = i16;
+impl DeviceInfo {
+    /// 设备的音频格式（帧时长取流水线默认值）
+    pub fn format(&self) -> AudioFormat {
+        AudioFormat::new(self.sample_rate, self.channels, crate::FRAME_SIZE_MS)
+    }
+}
+
+/// 音频采样类型（与 audio_types 中的定义保持一致）
+pub use crate::audio_types::AudioSample;
 
 /// 音频设备抽象 trait
 pub trait AudioDevice: Send + Sync {
@@ -62,6 +70,16 @@ pub trait AudioDevice: Send + Sync {
     
     /// 检查输入流是否正在运行
     fn is_recording(&self) -> bool;
+
+    /// 输入流回调交付的音频格式
+    fn input_format(&self) -> AudioFormat {
+        self.get_default_input_device().format()
+    }
+
+    /// 播放接口接受的音频格式
+    fn output_format(&self) -> AudioFormat {
+        self.get_default_output_device().format()
+    }
 }
 
 /// 模拟音频设备实现
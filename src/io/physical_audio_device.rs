@@ -1,18 +1,30 @@
 //! 物理音频设备实现
 //! 连接到真实的音频硬件设备
 
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    Device, Host, SupportedStreamConfig,
+    Device,
 };
 use crate::audio_types::{AudioSample, SAMPLE_RATE, CHANNELS};
+use crate::core::sample::{to_float_buffer, Ditherer, Sample};
+use crate::core::spsc_ring_buffer::{RingConsumer, RingProducer, SpscRingBuffer};
+use crate::dsp::channel_mixer::{ChannelMapping, Downmixer, Upmixer};
+use crate::dsp::resampler::Resampler;
+
+/// 播放队列可容纳的音频时长（秒）
+const OUTPUT_QUEUE_SECONDS: usize = 30;
+
+/// 输出回调每次从播放队列批量读取的最大帧数，更大的设备缓冲区分块读取
+const OUTPUT_BLOCK_FRAMES: usize = 1024;
 
 pub struct PhysicalAudioDevice {
     input_device: Option<Device>,
     output_device: Option<Device>,
     sample_rate: u32,
     channels: u16,
+    /// 设备声道与流水线单声道之间的映射方式
+    channel_mapping: ChannelMapping,
 }
 
 impl PhysicalAudioDevice {
@@ -33,9 +45,20 @@ impl PhysicalAudioDevice {
             output_device: Some(output_device),
             sample_rate: SAMPLE_RATE,
             channels: CHANNELS,
+            channel_mapping: ChannelMapping::default(),
         })
     }
 
+    /// 设置声道映射方式（在打开音频流之前调用）
+    pub fn set_channel_mapping(&mut self, mapping: ChannelMapping) {
+        self.channel_mapping = mapping;
+    }
+
+    /// 获取当前的声道映射方式
+    pub fn channel_mapping(&self) -> &ChannelMapping {
+        &self.channel_mapping
+    }
+
     pub fn list_input_devices() -> Result<Vec<(String, Device)>, Box<dyn std::error::Error>> {
         let host = cpal::default_host();
         let devices = host.input_devices()?
@@ -102,7 +125,7 @@ impl PhysicalAudioDevice {
         }
     }
 
-    pub fn open_input_stream<F>(&self, callback: F) -> Result<Box<dyn Stream>, Box<dyn std::error::Error>>
+    pub fn open_input_stream<F>(&self, callback: F) -> Result<cpal::Stream, Box<dyn std::error::Error>>
     where
         F: FnMut(&[AudioSample]) + Send + 'static,
     {
@@ -121,12 +144,16 @@ impl PhysicalAudioDevice {
             cpal::SampleFormat::U16 => {
                 self.build_input_stream::<u16>(device, &config.into(), callback, err_fn)?
             }
+            cpal::SampleFormat::I32 => {
+                self.build_input_stream::<i32>(device, &config.into(), callback, err_fn)?
+            }
             cpal::SampleFormat::F32 => {
                 self.build_input_stream::<f32>(device, &config.into(), callback, err_fn)?
             }
+            format => return Err(format!("Unsupported input sample format: {:?}", format).into()),
         };
         
-        Ok(Box::new(stream))
+        Ok(stream)
     }
 
     fn build_input_stream<T>(
@@ -137,17 +164,34 @@ impl PhysicalAudioDevice {
         err_fn: impl Fn(cpal::StreamError) + Send + 'static,
     ) -> Result<cpal::Stream, Box<dyn std::error::Error>>
     where
-        T: cpal::Sample,
-        AudioSample: From<T>,
+        T: cpal::SizedSample + Sample,
     {
         let channels = config.channels as usize;
         
+        // 设备声道 -> 单声道，再从设备采样率 -> 流水线采样率（例如 48kHz -> 16kHz）
+        let mut downmixer = Downmixer::new(self.channel_mapping.downmix.clone(), config.channels);
+        let mut resampler = Resampler::new(config.sample_rate.0, self.sample_rate, self.channels);
+        let mut ditherer = Ditherer::new();
+        let mut converted = Vec::new();
+        let mut mono = Vec::new();
+        let mut resampled = Vec::new();
+        let mut samples: Vec<AudioSample> = Vec::new();
+        
         let stream = device.build_input_stream(
             config,
             move |data: &[T], _: &cpal::InputCallbackInfo| {
-                let samples: Vec<AudioSample> = data.iter()
-                    .map(|&sample| sample.into())
-                    .collect();
+                converted.clear();
+                converted.extend(data.iter().map(|&sample| Sample::to_f32(sample)));
+                
+                mono.clear();
+                downmixer.process_into(&converted, &mut mono);
+                
+                resampled.clear();
+                resampler.process_into(&mono, &mut resampled);
+                
+                // 浮点处理完成后再带抖动量化为流水线的16位采样
+                samples.clear();
+                ditherer.quantize_into(&resampled, &mut samples);
                 
                 callback(&samples);
             },
@@ -155,6 +199,11 @@ impl PhysicalAudioDevice {
             None, // None means default stream flags
         )?;
         
+        println!(
+            "Input stream: {} Hz, {} channel(s) -> {} Hz mono ({:?})",
+            config.sample_rate.0, channels, self.sample_rate, self.channel_mapping.downmix
+        );
+        
         Ok(stream)
     }
 
@@ -164,54 +213,68 @@ impl PhysicalAudioDevice {
         // 获取设备支持的配置
         let config = device.default_output_config()
             .map_err(|_| "Failed to get default output config")?;
+        let device_rate = config.sample_rate().0;
         
         let err_fn = |err| eprintln!("An error occurred on the output audio stream: {}", err);
         
+        // 无锁播放队列，保存已转换到设备采样率的音频；消费端移入设备回调，回调中不加锁也不分配内存
+        let (producer, consumer) = SpscRingBuffer::with_capacity(device_rate as usize * OUTPUT_QUEUE_SECONDS);
+        
         let stream = match config.sample_format() {
             cpal::SampleFormat::I16 => {
-                self.build_output_stream::<i16>(device, &config.into(), err_fn)?
+                self.build_output_stream::<i16>(device, &config.into(), consumer, err_fn)?
             }
             cpal::SampleFormat::U16 => {
-                self.build_output_stream::<u16>(device, &config.into(), err_fn)?
+                self.build_output_stream::<u16>(device, &config.into(), consumer, err_fn)?
+            }
+            cpal::SampleFormat::I32 => {
+                self.build_output_stream::<i32>(device, &config.into(), consumer, err_fn)?
             }
             cpal::SampleFormat::F32 => {
-                self.build_output_stream::<f32>(device, &config.into(), err_fn)?
+                self.build_output_stream::<f32>(device, &config.into(), consumer, err_fn)?
             }
+            format => return Err(format!("Unsupported output sample format: {:?}", format).into()),
         };
         
-        Ok(OutputStreamHandle::new(stream))
+        // 流水线采样率 -> 设备采样率（例如 16kHz -> 48kHz）
+        let resampler = Resampler::new(self.sample_rate, device_rate, self.channels);
+        
+        Ok(OutputStreamHandle::new(stream, producer, resampler))
     }
 
     fn build_output_stream<T>(
         &self,
         device: &Device,
         config: &cpal::StreamConfig,
+        mut output_buffer: RingConsumer<f32>,
         err_fn: impl Fn(cpal::StreamError) + Send + 'static,
     ) -> Result<cpal::Stream, Box<dyn std::error::Error>>
     where
-        T: cpal::Sample,
-        AudioSample: Into<T>,
+        T: cpal::SizedSample + Sample,
     {
-        let sample_rate = config.sample_rate.0;
         let channels = config.channels as usize;
         
-        // 创建一个共享缓冲区用于输出
-        let output_buffer = Arc::new(Mutex::new(Vec::new()));
-        let buffer_clone = output_buffer.clone();
+        // 单声道 -> 设备声道布局
+        let upmixer = Upmixer::new(&self.channel_mapping.upmix, config.channels);
+        let mut device_frame = vec![0.0f32; channels];
+        let mut ditherer = Ditherer::new();
+        // 批量读取的块缓冲在打开流之前分配，回调中不分配内存
+        let mut block = vec![0.0f32; OUTPUT_BLOCK_FRAMES];
         
         let stream = device.build_output_stream(
             config,
             move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
-                let buffer = buffer_clone.lock().unwrap();
-                let mut sample_iter = buffer.iter();
-                
-                for frame in data.chunks_mut(channels) {
-                    let sample = sample_iter.next()
-                        .copied()
-                        .unwrap_or(0i16); // 默认静音值
-                        
-                    for sample_out in frame.iter_mut() {
-                        *sample_out = sample.into();
+                for chunk in data.chunks_mut(channels * OUTPUT_BLOCK_FRAMES) {
+                    let frames = chunk.len() / channels;
+                    let read = output_buffer.read(&mut block[..frames]);
+                    block[read..frames].fill(0.0); // 队列不足时补静音
+
+                    for (frame, &sample) in chunk.chunks_mut(channels).zip(block.iter()) {
+                        upmixer.write_frame(sample, &mut device_frame);
+
+                        for (sample_out, &value) in frame.iter_mut().zip(device_frame.iter()) {
+                            *sample_out = ditherer.quantize::<T>(value);
+                        }
                     }
                 }
             },
@@ -226,14 +289,14 @@ impl PhysicalAudioDevice {
 // 输出流句柄，用于播放音频
 pub struct OutputStreamHandle {
     stream: cpal::Stream,
-    buffer: Arc<Mutex<Vec<AudioSample>>>,
+    queue: Mutex<OutputQueue>,
 }
 
 impl OutputStreamHandle {
-    fn new(stream: cpal::Stream) -> Self {
+    fn new(stream: cpal::Stream, buffer: RingProducer<f32>, resampler: Resampler) -> Self {
         OutputStreamHandle {
             stream,
-            buffer: Arc::new(Mutex::new(Vec::new())),
+            queue: Mutex::new(OutputQueue { buffer, resampler }),
         }
     }
 
@@ -247,13 +310,47 @@ impl OutputStreamHandle {
         Ok(())
     }
 
+    /// 播放流水线采样率的音频，内部转换为设备采样率后加入播放队列
+    ///
+    /// 重采样器会保留最后 `latency_frames()` 帧等待后续音频；一段音频的最后一块用 [`Self::play_audio_final`] 送入。
     pub fn play_audio(&self, audio_data: &[AudioSample]) -> Result<usize, Box<dyn std::error::Error>> {
-        {
-            let mut buffer = self.buffer.lock().unwrap();
-            buffer.clear();
-            buffer.extend_from_slice(audio_data);
+        self.queue.lock().unwrap().push(audio_data, false)
+    }
+
+    /// 播放一段音频的最后一块：冲刷重采样器，使整段音频完整进入播放队列
+    pub fn play_audio_final(&self, audio_data: &[AudioSample]) -> Result<usize, Box<dyn std::error::Error>> {
+        self.queue.lock().unwrap().push(audio_data, true)
+    }
+
+    /// 播放队列中尚未输出的设备采样帧数
+    pub fn queued_frames(&self) -> usize {
+        self.queue.lock().unwrap().buffer.len()
+    }
+}
+
+/// 播放队列的生产端及其前面的重采样器
+struct OutputQueue {
+    buffer: RingProducer<f32>,
+    resampler: Resampler,
+}
+
+impl OutputQueue {
+    /// 转换为设备采样率后写入队列，`end_of_clip` 时冲刷重采样器中剩余的尾部
+    fn push(&mut self, audio_data: &[AudioSample], end_of_clip: bool) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut resampled = self.resampler.process(&to_float_buffer(audio_data));
+        if end_of_clip {
+            resampled.extend(self.resampler.flush());
         }
-        
+
+        let written = self.buffer.write(&resampled);
+        if written < resampled.len() {
+            return Err(format!(
+                "Output queue full, dropped {} of {} samples",
+                resampled.len() - written,
+                resampled.len()
+            ).into());
+        }
+
         Ok(audio_data.len())
     }
 }
@@ -284,4 +381,21 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_final_clip_plays_at_full_length() {
+        let (buffer, consumer) = SpscRingBuffer::with_capacity(48000);
+        let mut queue = OutputQueue { buffer, resampler: Resampler::new(16000, 48000, 1) };
+
+        // 不冲刷时重采样器留下尾部
+        queue.push(&[8000; 1600], false).unwrap();
+        assert!(consumer.len() < 4800);
+
+        // 最后一块送入后整段音频都进入播放队列（输出位置累加的舍入误差最多多出一帧），下一段从头开始
+        queue.push(&[8000; 1600], true).unwrap();
+        let clip = consumer.len();
+        assert!(clip.abs_diff(9600) <= 1, "{}", clip);
+        queue.push(&[8000; 160], true).unwrap();
+        assert!((consumer.len() - clip).abs_diff(480) <= 1, "{}", consumer.len() - clip);
+    }
+}
@@ -0,0 +1,328 @@
+//! WAV（RIFF）文件读写模块
+//! 解析PCM16/PCM24/float32/µ-law格式的WAV文件，并转换为系统内部的16kHz单声道采样
+
+use std::path::Path;
+use crate::dsp::resampler::Resampler;
+use crate::{AudioSample, Sample, SAMPLE_RATE};
+
+/// WAV读写结果类型
+pub type WavResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// WAV格式标签
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_MULAW: u16 = 0x0007;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// WAV采样编码格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WavSampleFormat {
+    Pcm16,    // 16位有符号整数
+    Pcm24,    // 24位有符号整数
+    Float32,  // 32位浮点
+    MuLaw,    // 8位µ-law压缩
+}
+
+impl WavSampleFormat {
+    /// 每个采样占用的字节数
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            WavSampleFormat::Pcm16 => 2,
+            WavSampleFormat::Pcm24 => 3,
+            WavSampleFormat::Float32 => 4,
+            WavSampleFormat::MuLaw => 1,
+        }
+    }
+}
+
+/// WAV文件格式描述
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavSpec {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: WavSampleFormat,
+}
+
+/// 解码后的WAV音频
+#[derive(Debug, Clone)]
+pub struct WavAudio {
+    pub spec: WavSpec,
+    pub samples: Vec<f32>,  // 交错排列的采样，范围 [-1.0, 1.0]
+}
+
+impl WavAudio {
+    /// 每个声道的帧数
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.spec.channels.max(1) as usize
+    }
+
+    /// 音频时长（秒）
+    pub fn duration_secs(&self) -> f64 {
+        self.frame_count() as f64 / self.spec.sample_rate as f64
+    }
+
+    /// 转换为系统内部格式（16kHz单声道AudioSample）
+    pub fn to_audio_samples(&self) -> Vec<AudioSample> {
+        let mono = downmix_to_mono(&self.samples, self.spec.channels as usize);
+        let mut resampler = Resampler::new(self.spec.sample_rate, SAMPLE_RATE, 1);
+        let mut resampled = resampler.process(&mono);
+        resampled.extend(resampler.flush());
+        // 滤波器延迟会在尾部多出少量采样，截断到与原始时长一致
+        let expected = (mono.len() as u64 * SAMPLE_RATE as u64 / self.spec.sample_rate as u64) as usize;
+        resampled.truncate(expected);
+        resampled.into_iter().map(AudioSample::from_f32).collect()
+    }
+}
+
+/// 从字节数据解析WAV
+pub fn decode_wav(data: &[u8]) -> WavResult<WavAudio> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err("Not a RIFF/WAVE file".into());
+    }
+
+    let mut spec: Option<WavSpec> = None;
+    let mut payload: Option<&[u8]> = None;
+    let mut pos = 12;
+
+    // 逐个遍历chunk，未知chunk（LIST、fact等）直接跳过
+    while pos + 8 <= data.len() {
+        let id = &data[pos..pos + 4];
+        let size = read_u32(data, pos + 4) as usize;
+        let body_start = pos + 8;
+        // 部分录音软件写入的长度可能超出实际文件大小，这里截断到文件末尾
+        let body_end = body_start.saturating_add(size).min(data.len());
+        let body = &data[body_start..body_end];
+
+        match id {
+            b"fmt " => spec = Some(parse_fmt_chunk(body)?),
+            b"data" => payload = Some(body),
+            _ => {}
+        }
+
+        // chunk长度为奇数时后面有一个填充字节
+        pos = body_start.saturating_add(size).saturating_add(size & 1);
+    }
+
+    let spec = spec.ok_or("Missing fmt chunk")?;
+    let payload = payload.ok_or("Missing data chunk")?;
+
+    let frame_bytes = spec.format.bytes_per_sample() * spec.channels as usize;
+    let usable = payload.len() - payload.len() % frame_bytes;
+    let samples = decode_samples(&payload[..usable], spec.format);
+
+    Ok(WavAudio { spec, samples })
+}
+
+/// 从文件读取WAV
+pub fn read_wav_file<P: AsRef<Path>>(path: P) -> WavResult<WavAudio> {
+    let data = std::fs::read(path.as_ref())?;
+    decode_wav(&data)
+}
+
+/// 将单声道AudioSample编码为PCM16 WAV字节
+pub fn encode_wav(samples: &[AudioSample], sample_rate: u32) -> Vec<u8> {
+    let channels: u16 = 1;
+    let bits_per_sample: u16 = 16;
+    let block_align = channels * bits_per_sample / 8;
+    let byte_rate = sample_rate * block_align as u32;
+    let data_len = (samples.len() * 2) as u32;
+
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&byte_rate.to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits_per_sample.to_le_bytes());
+
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        out.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    out
+}
+
+/// 将单声道AudioSample写入WAV文件
+pub fn write_wav_file<P: AsRef<Path>>(path: P, samples: &[AudioSample], sample_rate: u32) -> WavResult<()> {
+    std::fs::write(path.as_ref(), encode_wav(samples, sample_rate))?;
+    Ok(())
+}
+
+/// 解析fmt chunk
+fn parse_fmt_chunk(body: &[u8]) -> WavResult<WavSpec> {
+    if body.len() < 16 {
+        return Err("fmt chunk too short".into());
+    }
+
+    let mut format_tag = read_u16(body, 0);
+    let channels = read_u16(body, 2);
+    let sample_rate = read_u32(body, 4);
+    let bits_per_sample = read_u16(body, 14);
+
+    // WAVE_FORMAT_EXTENSIBLE 的真实格式保存在SubFormat GUID的前两个字节
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        if body.len() < 26 {
+            return Err("Extensible fmt chunk too short".into());
+        }
+        format_tag = read_u16(body, 24);
+    }
+
+    if channels == 0 || sample_rate == 0 {
+        return Err(format!("Invalid fmt chunk: {} channels at {} Hz", channels, sample_rate).into());
+    }
+
+    let format = match (format_tag, bits_per_sample) {
+        (WAVE_FORMAT_PCM, 16) => WavSampleFormat::Pcm16,
+        (WAVE_FORMAT_PCM, 24) => WavSampleFormat::Pcm24,
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => WavSampleFormat::Float32,
+        (WAVE_FORMAT_MULAW, 8) => WavSampleFormat::MuLaw,
+        _ => {
+            return Err(format!(
+                "Unsupported WAV format: tag 0x{:04X}, {} bits",
+                format_tag, bits_per_sample
+            ).into())
+        }
+    };
+
+    Ok(WavSpec { sample_rate, channels, format })
+}
+
+/// 将原始数据解码为浮点采样
+fn decode_samples(payload: &[u8], format: WavSampleFormat) -> Vec<f32> {
+    let step = format.bytes_per_sample();
+    payload
+        .chunks_exact(step)
+        .map(|b| match format {
+            WavSampleFormat::Pcm16 => i16::from_le_bytes([b[0], b[1]]).to_f32(),
+            WavSampleFormat::Pcm24 => {
+                // 将24位数据放到i32高位，再右移完成符号扩展
+                let value = i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            WavSampleFormat::Float32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            WavSampleFormat::MuLaw => mulaw_to_linear(b[0]).to_f32(),
+        })
+        .collect()
+}
+
+/// G.711 µ-law 解码
+fn mulaw_to_linear(byte: u8) -> i16 {
+    let value = !byte;
+    let sign = value & 0x80;
+    let exponent = (value >> 4) & 0x07;
+    let mantissa = value & 0x0F;
+    let magnitude = ((((mantissa as i32) << 3) + 0x84) << exponent) - 0x84;
+    if sign != 0 { -magnitude as i16 } else { magnitude as i16 }
+}
+
+/// 多声道交错数据混合为单声道
+fn downmix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造一个带有额外chunk的WAV文件
+    fn build_wav(format_tag: u16, channels: u16, sample_rate: u32, bits: u16, payload: &[u8]) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&format_tag.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+
+        let mut body = Vec::new();
+        body.extend_from_slice(b"WAVE");
+        body.extend_from_slice(b"fmt ");
+        body.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+        body.extend_from_slice(&fmt);
+        // 奇数长度的LIST chunk，后跟填充字节
+        body.extend_from_slice(b"LIST");
+        body.extend_from_slice(&5u32.to_le_bytes());
+        body.extend_from_slice(b"INFOx\0");
+        body.extend_from_slice(b"fact");
+        body.extend_from_slice(&4u32.to_le_bytes());
+        body.extend_from_slice(&((payload.len() / block_align as usize) as u32).to_le_bytes());
+        body.extend_from_slice(b"data");
+        body.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        body.extend_from_slice(payload);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    #[test]
+    fn test_wav_roundtrip() {
+        let samples: Vec<AudioSample> = (0..1600).map(|i| ((i % 200) * 100 - 10000) as i16).collect();
+        let bytes = encode_wav(&samples, SAMPLE_RATE);
+
+        let audio = decode_wav(&bytes).unwrap();
+        assert_eq!(audio.spec.format, WavSampleFormat::Pcm16);
+        assert_eq!(audio.spec.sample_rate, SAMPLE_RATE);
+        assert_eq!(audio.to_audio_samples(), samples);
+    }
+
+    #[test]
+    fn test_decode_formats_with_extra_chunks() {
+        // PCM24 立体声，48kHz
+        let mut payload = Vec::new();
+        for _ in 0..480 {
+            payload.extend_from_slice(&[0x00, 0x00, 0x40]); // 左声道 0.5
+            payload.extend_from_slice(&[0x00, 0x00, 0xC0]); // 右声道 -0.5
+        }
+        let audio = decode_wav(&build_wav(WAVE_FORMAT_PCM, 2, 48000, 24, &payload)).unwrap();
+        assert_eq!(audio.spec.format, WavSampleFormat::Pcm24);
+        assert_eq!(audio.samples[0], 0.5);
+        assert_eq!(audio.samples[1], -0.5);
+        // 10ms的48kHz立体声应转换为160个16kHz单声道采样
+        let converted = audio.to_audio_samples();
+        assert_eq!(converted.len(), 160);
+        assert!(converted.iter().all(|&s| s == 0));
+
+        // float32 单声道
+        let payload: Vec<u8> = [0.25f32, -1.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let audio = decode_wav(&build_wav(WAVE_FORMAT_IEEE_FLOAT, 1, 16000, 32, &payload)).unwrap();
+        assert_eq!(audio.to_audio_samples(), vec![8192, -32768]);
+
+        // µ-law：0xFF为0，0x80为最大正值
+        let audio = decode_wav(&build_wav(WAVE_FORMAT_MULAW, 1, 8000, 8, &[0xFF, 0x80])).unwrap();
+        assert_eq!(audio.spec.format, WavSampleFormat::MuLaw);
+        assert_eq!(audio.samples[0], 0.0);
+        assert_eq!(mulaw_to_linear(0x80), 32124);
+    }
+
+    #[test]
+    fn test_decode_invalid_data() {
+        assert!(decode_wav(b"fake wav data").is_err());
+        assert!(decode_wav(&build_wav(WAVE_FORMAT_PCM, 1, 16000, 12, &[0, 0])).is_err());
+    }
+}
@@ -3,10 +3,19 @@
 
 pub mod audio_types;
 pub mod core {
+    pub mod framer;
     pub mod ring_buffer;
+    pub mod rng;
+    pub mod sample;
+    pub mod spsc_ring_buffer;
+    pub mod tap_buffer;
 }
 
-pub use core::ring_buffer::RingBuffer;
+pub use core::framer::Framer;
+pub use core::ring_buffer::{OverflowPolicy, RingBuffer, RingBufferStats};
+pub use core::sample::Sample;
+pub use core::spsc_ring_buffer::{RingConsumer, RingProducer, SpscRingBuffer};
+pub use core::tap_buffer::{LagPolicy, TapBuffer, TapError, TapReader};
 pub use audio_types::*;
 
 /// IO模块 - 负责音频输入输出
@@ -14,11 +23,33 @@ pub mod io {
     pub mod audio_device;
     pub mod virtual_audio_device;
     pub mod audio_capture;
+    pub mod wav;
+    pub mod physical_audio_device;
+}
+
+/// DSP模块 - 负责采样率转换、声道映射等音频信号处理
+pub mod dsp {
+    pub mod resampler;
+    pub mod channel_mixer;
+    pub mod processor;
+    pub mod filters;
+    pub mod agc;
+    pub mod fft;
+    pub mod noise_suppressor;
+    pub mod echo_canceller;
+    pub mod loudness;
+    pub mod time_stretch;
+    pub mod ducking_mixer;
+    pub mod level_meter;
+    pub mod comfort_noise;
 }
 
 /// Engine模块 - 负责ASR、MT、TTS核心引擎
 pub mod engine {
     pub mod vad;
+    pub mod silero_vad;
+    pub mod gmm_vad;
+    pub mod model_loader;
     pub mod asr;
     pub mod mt;
     pub mod tts;
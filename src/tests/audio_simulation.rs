@@ -1,15 +1,17 @@
 //! 音频模拟测试模块
 //! 用于模拟真实的音频输入/输出场景
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs;
 use tokio::time::sleep;
 use crate::{
     bidirectional_translator::{BidirectionalResult, TranslationDirection},
+    engine::tts::Tts,
+    io::wav,
     virtual_audio_manager::AppContext,
-    AudioSample
+    AudioSample, SAMPLE_RATE
 };
 
 /// 音频模拟测试配置
@@ -50,6 +52,8 @@ pub struct AudioSimulationTester {
     pub app_context: Arc<tokio::sync::Mutex<AppContext>>,
     /// 翻译结果缓存
     translation_results: Arc<tokio::sync::Mutex<Vec<BidirectionalResult>>>,
+    /// 用于生成译文音频的TTS引擎
+    tts: Tts,
 }
 
 impl AudioSimulationTester {
@@ -72,10 +76,15 @@ impl AudioSimulationTester {
             results.push(result.clone());
         });
 
+        // 创建TTS引擎，用于将译文合成为WAV输出
+        let mut tts = Tts::new("./models/chattts.bin".to_string(), "chattts".to_string());
+        tts.initialize()?;
+
         Ok(AudioSimulationTester {
             config,
             app_context: Arc::new(tokio::sync::Mutex::new(app_context)),
             translation_results,
+            tts,
         })
     }
 
@@ -117,7 +126,7 @@ impl AudioSimulationTester {
             // 收集所有音频文件
             while let Ok(Some(entry)) = entries.next_entry().await {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm" || ext == "mp3") {
+                if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm") {
                     entries_vec.push(path);
                     has_files = true;
                 }
@@ -152,21 +161,7 @@ impl AudioSimulationTester {
 
                     for result in latest_results {
                         // 将翻译结果保存到源输出目录
-                        let output_filename = format!("translated_{}.txt", 
-                            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
-                        let output_path = self.config.source_output_dir.join(output_filename);
-                        
-                        tokio::fs::write(
-                            &output_path,
-                            format!(
-                                "原文: {}\n译文: {}\n时间: {:?}",
-                                result.original_text,
-                                result.translated_text,
-                                result.timestamp
-                            )
-                        ).await?;
-                        
-                        println!("已保存翻译结果到: {:?}", output_path);
+                        self.save_translation_output(&self.config.source_output_dir, &result).await?;
                     }
                 }
 
@@ -201,7 +196,7 @@ impl AudioSimulationTester {
             // 收集所有音频文件
             while let Ok(Some(entry)) = entries.next_entry().await {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm" || ext == "mp3") {
+                if path.extension().map_or(false, |ext| ext == "wav" || ext == "pcm") {
                     entries_vec.push(path);
                     has_files = true;
                 }
@@ -236,21 +231,7 @@ impl AudioSimulationTester {
 
                     for result in latest_results {
                         // 将翻译结果保存到目标输出目录
-                        let output_filename = format!("translated_{}.txt", 
-                            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
-                        let output_path = self.config.target_output_dir.join(output_filename);
-                        
-                        tokio::fs::write(
-                            &output_path,
-                            format!(
-                                "原文: {}\n译文: {}\n时间: {:?}",
-                                result.original_text,
-                                result.translated_text,
-                                result.timestamp
-                            )
-                        ).await?;
-                        
-                        println!("已保存翻译结果到: {:?}", output_path);
+                        self.save_translation_output(&self.config.target_output_dir, &result).await?;
                     }
                 }
 
@@ -306,21 +287,53 @@ impl AudioSimulationTester {
         &self.config
     }
 
-    /// 读取音频文件并返回模拟的音频数据
+    /// 保存翻译结果：文本写入 .txt，译文语音写入同名 .wav
+    async fn save_translation_output(
+        &self,
+        output_dir: &Path,
+        result: &BidirectionalResult,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let output_stem = format!("translated_{}",
+            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
+
+        let text_path = output_dir.join(format!("{}.txt", output_stem));
+        tokio::fs::write(
+            &text_path,
+            format!(
+                "原文: {}\n译文: {}\n时间: {:?}",
+                result.original_text,
+                result.translated_text,
+                result.timestamp
+            )
+        ).await?;
+        println!("已保存翻译结果到: {:?}", text_path);
+
+        // 合成译文语音并写入WAV文件
+        let tts_result = self.tts.generate_speech(&result.translated_text);
+        if tts_result.success {
+            let audio_path = output_dir.join(format!("{}.wav", output_stem));
+            tokio::fs::write(&audio_path, wav::encode_wav(&tts_result.audio_data, SAMPLE_RATE)).await?;
+            println!("已保存译文音频到: {:?}", audio_path);
+        }
+
+        Ok(())
+    }
+
+    /// 读取音频文件并转换为16kHz单声道音频数据
+    ///
+    /// `.wav` 文件按RIFF格式解析；`.pcm` 文件视为16kHz单声道的16位小端裸数据。
     pub async fn read_audio_file(&self, path: &PathBuf) -> Result<Vec<AudioSample>, Box<dyn std::error::Error + Send + Sync>> {
-        // 这里应该实现实际的音频文件读取逻辑
-        // 为了演示目的，我们生成模拟的音频数据
         println!("读取音频文件: {:?}", path);
-        
-        // 生成模拟音频数据
-        let mut audio_data = Vec::new();
-        for i in 0..1000 {
-            // 生成简单的模拟音频样本
-            let sample = (i % 1000) as AudioSample;
-            audio_data.push(sample);
+        let data = fs::read(path).await?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("pcm") => Ok(data
+                .chunks_exact(2)
+                .map(|b| AudioSample::from_le_bytes([b[0], b[1]]))
+                .collect()),
+            Some("wav") => Ok(wav::decode_wav(&data)?.to_audio_samples()),
+            _ => Err(format!("Unsupported audio file: {:?}", path).into()),
         }
-        
-        Ok(audio_data)
     }
 }
 #[cfg(test)]
@@ -352,13 +365,20 @@ mod tests {
         let config = AudioSimulationConfig::default();
         let tester = AudioSimulationTester::new(config).await.unwrap();
 
-        // 创建一个临时音频文件
+        // 创建一个临时音频文件（48kHz的1kHz正弦波）
         let temp_file = PathBuf::from("./tests/data/temp_test.wav");
-        tokio::fs::write(&temp_file, b"fake wav data").await.unwrap();
+        let tone: Vec<AudioSample> = (0..4800)
+            .map(|i| ((i as f32 * 2.0 * std::f32::consts::PI / 48.0).sin() * 8000.0) as AudioSample)
+            .collect();
+        wav::write_wav_file(&temp_file, &tone, 48000).unwrap();
 
-        let audio_data = tester.read_audio_file(&temp_file).await;
-        assert!(audio_data.is_ok());
-        assert!(!audio_data.unwrap().is_empty());
+        let audio_data = tester.read_audio_file(&temp_file).await.unwrap();
+        assert_eq!(audio_data.len(), 1600); // 100ms @ 16kHz
+        assert!(audio_data.iter().any(|&s| s.abs() > 4000));
+
+        // 非WAV内容应当报错
+        tokio::fs::write(&temp_file, b"fake wav data").await.unwrap();
+        assert!(tester.read_audio_file(&temp_file).await.is_err());
 
         // 清理临时文件
         tokio::fs::remove_file(temp_file).await.ok();
@@ -253,9 +253,9 @@ impl AppContext {
         println!("Application started with virtual devices:");
         println!("  Input device: {}", self.audio_manager.get_virtual_input_device_id());
         println!("  Output device: {}", self.audio_manager.get_virtual_output_device_id());
-        println!("  Language pair: {} <-> {}", 
-                 self.translator.lock().unwrap().get_current_language_pair().source,
-                 self.translator.lock().unwrap().get_current_language_pair().target);
+        // 先取出语言对再打印，避免在同一语句中重复加锁导致死锁
+        let pair = self.translator.lock().unwrap().get_current_language_pair();
+        println!("  Language pair: {} <-> {}", pair.source, pair.target);
 
         Ok(())
     }